@@ -0,0 +1,204 @@
+//! Compares finding what each shot touches by scanning every collider, as the game used to, against
+//! keeping the `CollisionGrid` up to date and asking it, as the game does every tick.
+//!
+//! The arena holds a full formation, the four shields and the ground, and shots among the invaders
+//! and inside every shield. Each shield is a single entity with a pixel mask, as in the game, or
+//! one entity per pixel, as shields used to be and as the grid was first built for. Each iteration
+//! marches one invader first, so the grid has a collider to move. Both only go as far as boxes:
+//! what a shot touches inside a shield's mask is the same work either way.
+//!
+//! Run with `cargo bench --bench collisions`.
+
+#![feature(test)]
+
+extern crate test;
+
+use bevy::{
+    math::bounding::{Aabb2d, BoundingVolume as _, IntersectsVolume as _},
+    prelude::*,
+};
+use bevy_space_invaders::{
+    enemy::Enemy,
+    headless_app,
+    shield::Shield,
+    shots::{
+        self, Collider, CollisionGrid, CollisionMatrix, EnemyShotKind, Hitbox, Layer, PixelMask,
+        Projectile,
+    },
+    GameState,
+};
+use test::{black_box, Bencher};
+
+/// Middle of each shield.
+const SHIELD_CENTERS: [Vec2; 4] = [
+    Vec2::new(44., 70.),
+    Vec2::new(90., 70.),
+    Vec2::new(135., 70.),
+    Vec2::new(179., 70.),
+];
+
+/// How far an invader marches each step.
+const MARCH_STEP: f32 = 2.;
+
+/// A game on its first tick of play, with a player shot and an enemy shot in every shield and a
+/// player shot on every fifth invader.
+fn full_arena() -> App {
+    let mut app = headless_app(1);
+
+    while *app.world().resource::<State<GameState>>() != GameState::Playing {
+        app.update();
+    }
+    app.update();
+
+    let world = app.world_mut();
+    let invaders: Vec<Vec2> = world
+        .query_filtered::<&Transform, With<Enemy>>()
+        .iter(world)
+        .step_by(5)
+        .map(|transform| transform.translation.truncate())
+        .collect();
+    for position in invaders {
+        shots::spawn_player_shots(world.commands(), position);
+    }
+    for center in SHIELD_CENTERS {
+        shots::spawn_player_shots(world.commands(), center);
+        shots::spawn_enemy_shots(world.commands(), center, EnemyShotKind::Rolling);
+    }
+    world.flush();
+
+    app
+}
+
+/// The same arena with every shield and the ground broken up into one entity per solid pixel.
+fn full_arena_with_pixel_shields() -> App {
+    let mut app = full_arena();
+    let world = app.world_mut();
+    let shields: Vec<(Entity, Aabb2d, PixelMask)> = world
+        .query_filtered::<(Entity, &Transform, &Hitbox, &PixelMask), With<Shield>>()
+        .iter(world)
+        .map(|(entity, transform, hitbox, mask)| (entity, hitbox.bounds(transform), mask.clone()))
+        .collect();
+
+    for (entity, bounds, mask) in shields {
+        world.despawn(entity);
+        for row in 0..mask.size().y {
+            for column in (0..mask.size().x).filter(|column| mask.get(*column, row)) {
+                let pixel = mask.pixel_aabb(bounds, column, row);
+                world.spawn((
+                    Transform::from_translation(pixel.center().extend(0.)),
+                    Hitbox(pixel.half_size() * 2.),
+                    Collider::new(Layer::SHIELD),
+                ));
+            }
+        }
+    }
+    world.run_system_cached(shots::update_grid).unwrap();
+
+    app
+}
+
+/// Moves the next invader a step, one per run like the game's march, back and forth so the
+/// formation stays in the arena however long the benchmark runs.
+fn march(mut step: Local<usize>, mut invaders: Query<&mut Transform, With<Enemy>>) {
+    let count = invaders.iter().len();
+    if let Some(mut transform) = invaders.iter_mut().nth(*step % count) {
+        let direction = if (*step / count).is_multiple_of(2) {
+            1.
+        } else {
+            -1.
+        };
+        transform.translation.x += MARCH_STEP * direction;
+    }
+    *step += 1;
+}
+
+fn aabb(transform: &Transform, hitbox: Option<&Hitbox>) -> Aabb2d {
+    Aabb2d::new(
+        transform.translation.truncate(),
+        hitbox
+            .map(|hitbox| hitbox.0 * transform.scale.truncate() / Vec2::new(2.0, 3.0))
+            .unwrap_or_else(|| transform.scale.truncate()),
+    )
+}
+
+fn scan(
+    matrix: Res<CollisionMatrix>,
+    projectiles: Query<(Entity, &Transform, &Hitbox, &Collider), With<Projectile>>,
+    colliders: Query<(Entity, &Transform, Option<&Hitbox>, &Collider)>,
+) -> usize {
+    let mut hits = 0;
+    for (projectile, transform, hitbox, collider) in projectiles.iter() {
+        let shot = aabb(transform, Some(hitbox));
+        for (other, other_transform, other_hitbox, other_collider) in colliders.iter() {
+            if projectile == other
+                || !matrix.collides(collider.layer, other_collider.layer)
+                || (transform.translation.y - other_transform.translation.y).abs() > 10.
+            {
+                continue;
+            }
+
+            if shot.intersects(&aabb(other_transform, other_hitbox)) {
+                hits += 1;
+            }
+        }
+    }
+    hits
+}
+
+fn grid(
+    grid: Res<CollisionGrid>,
+    matrix: Res<CollisionMatrix>,
+    projectiles: Query<(&Transform, &Hitbox, &Collider), With<Projectile>>,
+) -> usize {
+    projectiles
+        .iter()
+        .map(|(transform, hitbox, collider)| {
+            grid.hits(aabb(transform, Some(hitbox)), matrix.mask(collider.layer))
+                .len()
+        })
+        .sum()
+}
+
+fn bench_scan(b: &mut Bencher, mut app: App) {
+    let world = app.world_mut();
+    let march = world.register_system(march);
+    let scan = world.register_system(scan);
+
+    b.iter(|| {
+        world.run_system(march).unwrap();
+        black_box(world.run_system(scan).unwrap())
+    });
+}
+
+fn bench_grid(b: &mut Bencher, mut app: App) {
+    let world = app.world_mut();
+    let march = world.register_system(march);
+    let update_grid = world.register_system(shots::update_grid);
+    let grid = world.register_system(grid);
+
+    b.iter(|| {
+        world.run_system(march).unwrap();
+        world.run_system(update_grid).unwrap();
+        black_box(world.run_system(grid).unwrap())
+    });
+}
+
+#[bench]
+fn scan_every_collider(b: &mut Bencher) {
+    bench_scan(b, full_arena());
+}
+
+#[bench]
+fn update_and_query_collision_grid(b: &mut Bencher) {
+    bench_grid(b, full_arena());
+}
+
+#[bench]
+fn scan_every_collider_with_pixel_shields(b: &mut Bencher) {
+    bench_scan(b, full_arena_with_pixel_shields());
+}
+
+#[bench]
+fn update_and_query_collision_grid_with_pixel_shields(b: &mut Bencher) {
+    bench_grid(b, full_arena_with_pixel_shields());
+}
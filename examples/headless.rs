@@ -0,0 +1,38 @@
+//! Plays a one-player game without a window: the cannon sweeps across the arena firing as fast as
+//! it can, and the final score is printed once the game is over.
+
+use bevy::prelude::*;
+use bevy_space_invaders::{
+    headless_app,
+    player::{Lives, PlayerInput},
+    score::Score,
+    wave::Wave,
+    GameState,
+};
+
+/// Give up after ten minutes of game time.
+const MAX_FRAMES: usize = 10 * 60 * 60;
+
+fn main() {
+    let mut app = headless_app(1);
+
+    for frame in 0..MAX_FRAMES {
+        *app.world_mut().resource_mut::<PlayerInput>() = PlayerInput {
+            movement: if (frame / 180) % 2 == 0 { 1. } else { -1. },
+            fire: true,
+        };
+        app.update();
+
+        if *app.world().resource::<State<GameState>>() == GameState::GameOver {
+            break;
+        }
+    }
+
+    let world = app.world();
+    println!(
+        "score {} on wave {} with {} lives left",
+        world.resource::<Score>().0,
+        world.resource::<Wave>().0,
+        world.resource::<Lives>().0,
+    );
+}
@@ -11,6 +11,9 @@ pub const PLAYER_FLOOR_GAP: f32 = 45.0;
 pub const PLAYER_PADDING: f32 = 0.0;
 pub const PLAYER_PROJECTILE_SPEED: f32 = 4.0;
 pub const PLAYER_SPEED: f32 = 1.0;
+pub const PLAYER_LIVES: usize = 3;
+pub const PLAYER_DEATH_SECONDS: f32 = 1.0;
+pub const PLAYER_INVULNERABLE_SECONDS: f32 = 1.5;
 
 pub const ENEMY_SIZE: Vec2 = Vec2::splat(16.);
 pub const ENEMY_SPACING: f32 = 3.0;
@@ -1,153 +1,399 @@
-use std::time::Duration;
-
 use crate::{
     constants::*,
+    player::{Lives, Player},
+    rng::GameRng,
     score::Points,
-    shots::{self, Hit},
-    GameState,
+    shots::{self, EnemyShot, EnemyShotKind, Hit, Hitbox, MaskFromSprite},
+    turns::{Players, Stash},
+    wave::Wave,
+    GameState, InGame, NewGame, PlayState, TickSet,
 };
 use bevy::prelude::*;
-// use rand::Rng as _;
+use rand::Rng as _;
 
 pub struct EnemyPlugin;
 
-const INITIAL_MOVE_DELAY: f32 = 0.6;
-const FINAL_MOVE_DELAY: f32 = 0.1;
 const MOVE_X: f32 = 4.0;
 const MOVE_Y: f32 = 8.0;
 
+/// Columns (1-based, left to right) the plunger and squiggly shots are fired from, in order. The
+/// plunger shot walks the first 16 entries and the squiggly shot the last 15, like the arcade.
+const COLUMN_TABLE: [usize; 21] = [
+    1, 7, 1, 1, 1, 4, 11, 1, 6, 3, 1, 1, 11, 9, 2, 8, 2, 11, 4, 7, 10,
+];
+const PLUNGER_COLUMNS: std::ops::Range<usize> = 0..16;
+const SQUIGGLY_COLUMNS: std::ops::Range<usize> = 6..21;
+
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(EnemyDirection::default())
-            .insert_resource(MoveTimer {
-                timer: Timer::from_seconds(1., TimerMode::Repeating),
-            })
+        app.add_event::<FormationStepped>()
+            .insert_resource(EnemyDirection::default())
+            .insert_resource(MarchCursor::default())
             .insert_resource(ShootTimer::default())
+            .insert_resource(Stash::<StashedFormation>::default())
+            .add_systems(NewGame, reset)
             .add_systems(OnEnter(GameState::Playing), startup)
-            .add_systems(
-                Update,
-                (update_move_timer, move_enemies, update_temporaries)
-                    .run_if(in_state(GameState::Playing)),
-            )
+            .add_systems(OnEnter(GameState::TurnChange), stash_formation)
             .add_systems(
                 FixedUpdate,
-                (swap_enemy_direction, shoot).run_if(in_state(GameState::Playing)),
+                (
+                    (march, check_invasion).chain().in_set(TickSet::Move),
+                    shoot.in_set(TickSet::Fire),
+                )
+                    .run_if(in_state(PlayState::Running)),
+            );
+    }
+}
+
+/// Invader sprites and animation, explosions and their sound.
+pub struct EnemyPresentationPlugin;
+
+impl Plugin for EnemyPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_assets)
+            .add_observer(add_sprite)
+            .add_observer(explode)
+            .add_systems(
+                Update,
+                (
+                    animate_invaders,
+                    update_temporaries.run_if(
+                        in_state(GameState::Playing)
+                            .or(in_state(GameState::LevelTransition))
+                            .or(in_state(GameState::TurnChange)),
+                    ),
+                ),
             );
     }
 }
 
+#[derive(Resource)]
+struct EnemyAssets {
+    /// Image and atlas layout of each kind of invader, from the top row down.
+    atlases: [(Handle<Image>, Handle<TextureAtlasLayout>); 3],
+    explosion: Handle<Image>,
+    killed: Handle<AudioSource>,
+}
+
 #[derive(Resource)]
 struct ShootTimer(Timer);
 
+/// Which shot the invaders try next, and where each column table currently points.
+#[derive(Resource)]
+struct ShotSchedule {
+    next: EnemyShotKind,
+    plunger: usize,
+    squiggly: usize,
+}
+
+impl ShotSchedule {
+    /// Both tables start from a point drawn from `rng`, so waves don't all open with the same
+    /// columns.
+    fn new(rng: &mut GameRng) -> Self {
+        Self {
+            next: EnemyShotKind::default(),
+            plunger: rng.random_range(PLUNGER_COLUMNS),
+            squiggly: rng.random_range(SQUIGGLY_COLUMNS),
+        }
+    }
+
+    /// Next column (0-based) from a table, advancing and wrapping its cursor.
+    fn next_column(cursor: &mut usize, range: std::ops::Range<usize>) -> usize {
+        let column = COLUMN_TABLE[*cursor] - 1;
+        *cursor += 1;
+        if *cursor >= range.end {
+            *cursor = range.start;
+        }
+        column
+    }
+}
+
 #[derive(Component)]
 struct Shooter;
 
 #[derive(Component)]
 struct NonShooter;
 
+/// Despawns the entity once its timer runs out, e.g. for explosions.
 #[derive(Component)]
-struct Temporary {
+pub struct Temporary {
     timer: Timer,
 }
 
-#[derive(Resource)]
-struct MoveTimer {
-    timer: Timer,
+impl Temporary {
+    pub fn from_seconds(seconds: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+        }
+    }
+}
+
+/// Walks through the formation one invader per fixed tick, bottom row first and left to right,
+/// like the arcade. A full pass over the surviving invaders is one formation step, so the march
+/// speeds up on its own as invaders die.
+#[derive(Resource, Default)]
+struct MarchCursor {
+    /// March order of the invader moved last in the current pass.
+    last: Option<(usize, usize)>,
+    /// Whether an invader reached the wall during the current pass.
+    edge_reached: bool,
+    /// Whether the current pass steps down instead of sideways.
+    dropping: bool,
+}
+
+/// The invaders a player has yet to shoot down, and which way they were marching.
+struct StashedFormation {
+    invaders: Vec<StashedInvader>,
+    direction: EnemyDirection,
+}
+
+struct StashedInvader {
+    enemy: Enemy,
+    transform: Transform,
+    points: usize,
+    shooter: bool,
 }
 
 fn startup(
     mut cmds: Commands,
-    assets: Res<AssetServer>,
-    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
+    wave: Res<Wave>,
+    players: Res<Players>,
+    mut stashed: ResMut<Stash<StashedFormation>>,
+    mut rng: ResMut<GameRng>,
 ) {
-    // enemies
-    let enemy_atlas_a = assets.load("sprites/a.png");
-    let layout_a =
-        TextureAtlasLayout::from_grid(UVec2::splat(16), 2, 1, Some(UVec2::splat(1)), None);
-    let texture_atlas_layout_a = texture_atlas_layout.add(layout_a);
-
-    let enemy_atlas_b = assets.load("sprites/b.png");
-    let layout_b =
-        TextureAtlasLayout::from_grid(UVec2::new(22, 16), 2, 1, Some(UVec2::splat(1)), None);
-    let texture_atlas_layout_b = texture_atlas_layout.add(layout_b);
-
-    let enemy_atlas_c = assets.load("sprites/c.png");
-    let layout_c =
-        TextureAtlasLayout::from_grid(UVec2::new(24, 16), 2, 1, Some(UVec2::splat(1)), None);
-    let texture_atlas_layout_c = texture_atlas_layout.add(layout_c);
+    cmds.insert_resource(MarchCursor::default());
+    cmds.insert_resource(ShotSchedule::new(&mut rng));
+
+    if let Some(formation) = stashed.resume(&players) {
+        for invader in formation.invaders {
+            spawn_enemy(
+                &mut cmds,
+                invader.enemy,
+                invader.transform,
+                invader.points,
+                invader.shooter,
+            );
+        }
+        cmds.insert_resource(formation.direction);
+        return;
+    }
 
     // starting position for enemies
     let enemy_start = Vec2::new(
         ENEMY_SIZE.x / 2.0 + ENEMY_WALL_GAP,
-        -ENEMY_SIZE.y / 2.0 + ARENA_SIZE.y - 55.,
+        -ENEMY_SIZE.y / 2.0 + ARENA_SIZE.y - 55. - wave.start_drop(),
     );
     for y in 0..5 {
         let mut current_enemy_pos = enemy_start;
         current_enemy_pos.y -= (ENEMY_SIZE.y / 2. + 8.) * y as f32;
-        let atlas = match y {
-            0 => (&enemy_atlas_a, &texture_atlas_layout_a, Vec2::new(16., 16.)),
-            1 | 2 => (&enemy_atlas_b, &texture_atlas_layout_b, Vec2::new(22., 16.)),
-            _ => (&enemy_atlas_c, &texture_atlas_layout_c, Vec2::new(24., 16.)),
-        };
         let points = match y {
             0 => 30,
             1 | 2 => 20,
             _ => 10,
         };
         for x in 0..11 {
-            let mut sprite = Sprite::from_atlas_image(
-                atlas.0.clone(),
-                TextureAtlas {
-                    layout: atlas.1.clone(),
-                    index: 0,
-                },
-            );
-            sprite.custom_size = Some(atlas.2 / 2.);
-
-            let mut enemy = cmds.spawn((
+            spawn_enemy(
+                &mut cmds,
                 Enemy { x, y },
-                sprite,
                 Transform::from_translation(current_enemy_pos.extend(0.0)),
-                shots::Collider::enemy_layer(),
-                Points(points),
-            ));
+                points,
+                y == 4,
+            );
 
-            if y == 4 {
-                enemy.insert(Shooter);
-            } else {
-                enemy.insert(NonShooter);
-            }
+            current_enemy_pos.x += 12. + ENEMY_SPACING;
+        }
+    }
 
-            enemy.observe(on_hit);
+    cmds.insert_resource(EnemyDirection::default());
+}
 
-            current_enemy_pos.x += 12. + ENEMY_SPACING;
+fn spawn_enemy(
+    cmds: &mut Commands,
+    enemy: Enemy,
+    transform: Transform,
+    points: usize,
+    shooter: bool,
+) {
+    let mut enemy = cmds.spawn((
+        enemy,
+        StateScoped(InGame),
+        transform,
+        Hitbox(enemy.size()),
+        shots::Collider::new(shots::Layer::ENEMY),
+        Points(points),
+    ));
+
+    if shooter {
+        enemy.insert(Shooter);
+    } else {
+        enemy.insert(NonShooter);
+    }
+
+    enemy.observe(on_hit);
+}
+
+fn load_assets(
+    mut cmds: Commands,
+    assets: Res<AssetServer>,
+    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let mut atlas = |name: &str, size: UVec2| {
+        let layout = TextureAtlasLayout::from_grid(size, 2, 1, Some(UVec2::splat(1)), None);
+        (assets.load(name), texture_atlas_layout.add(layout))
+    };
+
+    cmds.insert_resource(EnemyAssets {
+        atlases: [
+            atlas("sprites/a.png", UVec2::splat(16)),
+            atlas("sprites/b.png", UVec2::new(22, 16)),
+            atlas("sprites/c.png", UVec2::new(24, 16)),
+        ],
+        explosion: assets.load("sprites/enemy-explosion.png"),
+        killed: assets.load("sounds/enemy-killed.ogg"),
+    });
+}
+
+fn add_sprite(
+    trigger: Trigger<OnAdd, Enemy>,
+    mut cmds: Commands,
+    assets: Res<EnemyAssets>,
+    enemies: Query<(&Enemy, &Hitbox)>,
+) {
+    let entity = trigger.entity();
+    let Ok((enemy, hitbox)) = enemies.get(entity) else {
+        return;
+    };
+
+    let (image, layout) = &assets.atlases[match enemy.y {
+        0 => 0,
+        1 | 2 => 1,
+        _ => 2,
+    }];
+    let mut sprite = Sprite::from_atlas_image(
+        image.clone(),
+        TextureAtlas {
+            layout: layout.clone(),
+            index: 0,
+        },
+    );
+    sprite.custom_size = Some(hitbox.0);
+
+    cmds.entity(entity)
+        .insert((sprite, MaskFromSprite::default()));
+}
+
+/// Invaders switch between their two frames every time they move.
+fn animate_invaders(mut enemies: Query<(Ref<Transform>, &mut Sprite), With<Enemy>>) {
+    for (transform, mut sprite) in enemies.iter_mut() {
+        if !transform.is_changed() || transform.is_added() {
+            continue;
+        }
+
+        if let Some(atlas) = &mut sprite.texture_atlas {
+            atlas.index = 1 - atlas.index;
         }
     }
+}
 
-    let _ = assets.load::<AudioSource>("sounds/enemy-killed.ogg");
+fn explode(
+    trigger: Trigger<Hit>,
+    mut cmds: Commands,
+    assets: Res<EnemyAssets>,
+    enemies: Query<&Transform, With<Enemy>>,
+) {
+    let Ok(enemy) = enemies.get(trigger.entity()) else {
+        return;
+    };
 
+    cmds.spawn((
+        Sprite {
+            image: assets.explosion.clone(),
+            custom_size: Some(Vec2::new(12., 8.)),
+            ..default()
+        },
+        Transform::from_xyz(enemy.translation.x, enemy.translation.y, 0.0),
+        Temporary::from_seconds(0.5),
+        StateScoped(InGame),
+    ));
+
+    cmds.spawn(AudioPlayer::new(assets.killed.clone()));
+}
+
+fn reset(mut cmds: Commands) {
     cmds.insert_resource(EnemyDirection::default());
-    cmds.insert_resource(MoveTimer {
-        timer: Timer::from_seconds(1., TimerMode::Repeating),
-    });
+    cmds.insert_resource(MarchCursor::default());
+    cmds.insert_resource(ShootTimer::default());
+    cmds.insert_resource(Stash::<StashedFormation>::default());
 }
 
-#[derive(Component, Default, Debug)]
+fn stash_formation(
+    mut cmds: Commands,
+    players: Res<Players>,
+    direction: Res<EnemyDirection>,
+    mut stashed: ResMut<Stash<StashedFormation>>,
+    enemies: Query<(Entity, &Enemy, &Transform, Has<Shooter>)>,
+    mut points: Query<&mut Points>,
+) {
+    if !players.is_swapping() {
+        return;
+    }
+
+    let mut invaders = Vec::new();
+    for (entity, enemy, transform, shooter) in enemies.iter() {
+        let Ok(mut points) = points.get_mut(entity) else {
+            continue;
+        };
+
+        invaders.push(StashedInvader {
+            enemy: *enemy,
+            transform: *transform,
+            points: points.0,
+            shooter,
+        });
+
+        // putting the invader aside must not award its points
+        points.0 = 0;
+        cmds.entity(entity).despawn();
+    }
+
+    stashed.put_aside(
+        &players,
+        StashedFormation {
+            invaders,
+            direction: *direction,
+        },
+    );
+}
+
+#[derive(Component, Default, Debug, Clone, Copy)]
 pub struct Enemy {
     x: usize,
     y: usize,
 }
 
-#[derive(Debug, Default, Resource, PartialEq, Eq)]
+impl Enemy {
+    /// Sort key for the march: bottom row first, then left to right.
+    fn march_order(&self) -> (usize, usize) {
+        (4 - self.y, self.x)
+    }
+
+    /// Size of the invaders in this one's row, which get wider towards the bottom.
+    fn size(&self) -> Vec2 {
+        match self.y {
+            0 => Vec2::new(8., 8.),
+            1 | 2 => Vec2::new(11., 8.),
+            _ => Vec2::new(12., 8.),
+        }
+    }
+}
+
+#[derive(Debug, Default, Resource, PartialEq, Eq, Clone, Copy)]
 pub enum EnemyDirection {
     #[default]
     Right,
     Left,
 }
 
-#[derive(Event, Default)]
-pub struct EnemyDirectionChanged;
-
 impl EnemyDirection {
     pub fn reverse(&mut self) {
         if *self == EnemyDirection::Right {
@@ -165,59 +411,79 @@ impl EnemyDirection {
     }
 }
 
-fn update_move_timer(
-    mut timer: ResMut<MoveTimer>,
-    time: Res<Time>,
-    enemies: Query<Entity, With<Enemy>>,
-) {
-    let enemy_count = enemies.iter().count();
-    let percent = 1. - enemy_count as f32 / (11 * 5) as f32;
-    let delay = INITIAL_MOVE_DELAY + (FINAL_MOVE_DELAY - INITIAL_MOVE_DELAY) * percent;
-    timer.timer.set_duration(Duration::from_secs_f32(delay));
-    timer.timer.tick(time.delta());
-}
-
-fn move_enemies(
-    direction: Res<EnemyDirection>,
-    mut transforms: Query<&mut Transform, With<Enemy>>,
-    mut sprites: Query<&mut Sprite, With<Enemy>>,
-    timer: Res<MoveTimer>,
+/// Sent every time each surviving invader has moved once.
+#[derive(Event, Default)]
+pub struct FormationStepped;
+
+/// Moves the next invader in march order, one per tick.
+fn march(
+    mut stepped: EventWriter<FormationStepped>,
+    mut cursor: ResMut<MarchCursor>,
+    mut direction: ResMut<EnemyDirection>,
+    mut enemies: Query<(&Enemy, &mut Transform)>,
 ) {
-    if timer.timer.finished() {
-        for mut enemy in transforms.iter_mut() {
-            enemy.translation.x += MOVE_X * direction.as_f32();
-        }
-        for mut sprite in sprites.iter_mut() {
-            if let Some(atlas) = &mut sprite.texture_atlas {
-                atlas.index = 1 - atlas.index;
-            }
+    let mut next = enemies
+        .iter()
+        .map(|(enemy, ..)| enemy.march_order())
+        .filter(|order| cursor.last.is_none_or(|last| *order > last))
+        .min();
+
+    if next.is_none() {
+        // every surviving invader moved: that was one formation step
+        stepped.send_default();
+        cursor.dropping = cursor.edge_reached;
+        if cursor.edge_reached {
+            direction.reverse();
         }
+        cursor.edge_reached = false;
+
+        next = enemies.iter().map(|(enemy, ..)| enemy.march_order()).min();
     }
-}
 
-fn swap_enemy_direction(
-    current: ResMut<EnemyDirection>,
-    mut enemies: Query<&mut Transform, With<Enemy>>,
-) {
-    let direction = current.into_inner();
-    let mut needs_reverse = false;
-    for enemy in enemies.iter() {
-        let x = enemy.translation.x;
-        needs_reverse = match direction {
-            EnemyDirection::Right => x + ENEMY_SIZE.x / 2. + ENEMY_WALL_GAP >= ARENA_SIZE.x,
-            EnemyDirection::Left => x - ENEMY_SIZE.x / 2. - ENEMY_WALL_GAP <= 0.,
-        };
+    let Some(next) = next else {
+        // the formation was just cleared
+        return;
+    };
+    cursor.last = Some(next);
 
-        if needs_reverse {
-            break;
-        }
+    let Some((_, mut transform)) = enemies
+        .iter_mut()
+        .find(|(enemy, ..)| enemy.march_order() == next)
+    else {
+        return;
+    };
+
+    if cursor.dropping {
+        transform.translation.y -= MOVE_Y;
+    } else {
+        transform.translation.x += MOVE_X * direction.as_f32();
     }
 
-    if needs_reverse {
-        direction.reverse();
-        for mut enemy in enemies.iter_mut() {
-            enemy.translation.y -= MOVE_Y;
-        }
+    let x = transform.translation.x;
+    let at_edge = match *direction {
+        EnemyDirection::Right => x + ENEMY_SIZE.x / 2. + ENEMY_WALL_GAP >= ARENA_SIZE.x,
+        EnemyDirection::Left => x - ENEMY_SIZE.x / 2. - ENEMY_WALL_GAP <= 0.,
+    };
+    if at_edge {
+        cursor.edge_reached = true;
+    }
+}
+
+/// An invader reaching the cannon's row ends the game at once, whatever lives are left, and in
+/// two-player games for the waiting player too.
+fn check_invasion(
+    invaders: Query<(&Transform, &Hitbox), With<Enemy>>,
+    mut lives: ResMut<Lives>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let player_top = PLAYER_FLOOR_GAP + PLAYER_SIZE.y / 2.;
+    let invaded = invaders
+        .iter()
+        .any(|(transform, hitbox)| transform.translation.y - hitbox.0.y / 2. <= player_top);
+
+    if invaded {
+        lives.0 = 0;
+        next_game_state.set(GameState::GameOver);
     }
 }
 
@@ -227,65 +493,96 @@ impl Default for ShootTimer {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn shoot(
     cmds: Commands,
-    assets: Res<shots::EnemyShotSpritesWithAtlas>,
     time: Res<Time>,
     mut timer: ResMut<ShootTimer>,
-    enemies: Query<&mut Transform, With<Shooter>>,
+    mut schedule: ResMut<ShotSchedule>,
+    shooters: Query<(&Enemy, &Transform), With<Shooter>>,
+    enemies: Query<(), With<Enemy>>,
+    shots_in_flight: Query<&EnemyShot>,
+    player: Single<&Transform, With<Player>>,
 ) {
     timer.0.tick(time.delta());
+    if !timer.0.finished() {
+        return;
+    }
+
+    // the kinds take turns, whether or not this one ends up firing
+    let kind = schedule.next;
+    schedule.next = kind.next();
 
-    if timer.0.finished() {
-        timer.0.reset();
+    if shots_in_flight.iter().any(|shot| shot.0 == kind) {
+        return;
+    }
 
-        // let mut rng = rand::rng();
-        // let rand = rng.random_range(0..enemies.iter().len());
-        let rand = 0;
+    let shooter = match kind {
+        EnemyShotKind::Rolling => {
+            // the closest column, as long as it is actually above the player
+            let player_x = player.translation.x;
+            shooters
+                .iter()
+                .map(|(_, transform)| transform)
+                .filter(|t| (t.translation.x - player_x).abs() <= (12. + ENEMY_SPACING) / 2.)
+                .min_by(|a, b| {
+                    let a = (a.translation.x - player_x).abs();
+                    let b = (b.translation.x - player_x).abs();
+                    a.total_cmp(&b)
+                })
+        }
+        // the plunger shot isn't used against the very last invader
+        EnemyShotKind::Plunger if enemies.iter().count() == 1 => None,
+        EnemyShotKind::Plunger => {
+            let column = ShotSchedule::next_column(&mut schedule.plunger, PLUNGER_COLUMNS);
+            find_shooter(&shooters, column)
+        }
+        EnemyShotKind::Squiggly => {
+            let column = ShotSchedule::next_column(&mut schedule.squiggly, SQUIGGLY_COLUMNS);
+            find_shooter(&shooters, column)
+        }
+    };
 
-        let enemy = enemies.iter().nth(rand).unwrap();
+    // an empty column means no shot this time
+    let Some(shooter) = shooter else {
+        return;
+    };
+
+    timer.0.reset();
+    shots::spawn_enemy_shots(
+        cmds,
+        Vec2::new(
+            shooter.translation.x,
+            shooter.translation.y - ENEMY_SIZE.y / 2.,
+        ),
+        kind,
+    );
+}
 
-        shots::spawn_enemy_shots(
-            cmds,
-            assets,
-            Vec2::new(enemy.translation.x, enemy.translation.y - ENEMY_SIZE.y / 2.),
-        );
-    }
+fn find_shooter<'a>(
+    shooters: &'a Query<(&Enemy, &Transform), With<Shooter>>,
+    column: usize,
+) -> Option<&'a Transform> {
+    shooters
+        .iter()
+        .find(|(enemy, _)| enemy.x == column)
+        .map(|(_, transform)| transform)
 }
 
 fn on_hit(
     trigger: Trigger<Hit>,
     mut cmds: Commands,
-    assets: Res<AssetServer>,
-    all_enemies: Query<&Transform, With<Enemy>>,
+    all_enemies: Query<(), With<Enemy>>,
     shooters: Query<&Enemy, With<Shooter>>,
     non_shooters: Query<(Entity, &Enemy), Without<Shooter>>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
     let entity = trigger.entity();
-    let enemy = all_enemies.get(entity).unwrap();
 
     if all_enemies.iter().count() == 1 {
-        next_game_state.set(GameState::GameOver);
-        return;
+        next_game_state.set(GameState::LevelTransition);
     }
 
-    // spawn explosion
-    cmds.spawn((
-        Sprite {
-            image: assets.load("sprites/enemy-explosion.png"),
-            custom_size: Some(Vec2::new(12., 8.)),
-            ..default()
-        },
-        Transform::from_xyz(enemy.translation.x, enemy.translation.y, 0.0),
-        Temporary {
-            timer: Timer::from_seconds(0.5, TimerMode::Once),
-        },
-    ));
-
-    // play sound
-    cmds.spawn(AudioPlayer::new(assets.load("sounds/enemy-killed.ogg")));
-
     // despawn enemy
     cmds.entity(entity).despawn();
 
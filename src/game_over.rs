@@ -1,6 +1,9 @@
 use bevy::{prelude::*, text::FontSmoothing};
 
-use crate::GameState;
+use crate::{
+    input::{Action, Actions},
+    GameState,
+};
 
 pub struct GameOverPlugin;
 
@@ -26,6 +29,7 @@ fn setup(mut cmds: Commands, asset_server: Res<AssetServer>) {
 
     cmds.spawn((
         GameOverScreen,
+        StateScoped(GameState::GameOver),
         Node {
             width: Percent(100.),
             height: Percent(99.),
@@ -42,17 +46,8 @@ fn setup(mut cmds: Commands, asset_server: Res<AssetServer>) {
     });
 }
 
-fn input(
-    mut cmds: Commands,
-    mut next_game_state: ResMut<NextState<GameState>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    entities: Query<Entity, (With<Transform>, Without<Camera>)>,
-) {
-    if keyboard.just_pressed(KeyCode::Space) {
+fn input(actions: Actions, mut next_game_state: ResMut<NextState<GameState>>) {
+    if actions.just_pressed(Action::Start) || actions.just_pressed(Action::Fire) {
         next_game_state.set(GameState::MainMenu);
-
-        for entity in entities.iter() {
-            cmds.entity(entity).despawn();
-        }
     }
 }
@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use crate::{enemy::FormationStepped, synth::Tone};
+use bevy::prelude::*;
+
+pub struct HeartbeatPlugin;
+
+/// The four descending bass notes the cabinet cycles through as the invaders march.
+const NOTES: [f32; 4] = [110., 98., 87., 82.];
+const NOTE_SECONDS: f32 = 0.1;
+/// Like the cabinet, notes are at least five ticks apart however fast the formation steps, so the
+/// last few invaders don't turn the heartbeat into a drone.
+const MIN_INTERVAL: Duration = Duration::from_nanos(1_000_000_000 * 5 / 60);
+
+impl Plugin for HeartbeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, startup)
+            .add_systems(Update, play.run_if(on_event::<FormationStepped>));
+    }
+}
+
+#[derive(Resource)]
+struct Heartbeat {
+    notes: [Handle<Tone>; 4],
+    next: usize,
+    /// When the last note started.
+    last: Option<Duration>,
+}
+
+fn startup(mut cmds: Commands, mut tones: ResMut<Assets<Tone>>) {
+    let notes = NOTES.map(|frequency| {
+        tones.add(Tone::new(frequency).with_duration(Duration::from_secs_f32(NOTE_SECONDS)))
+    });
+
+    cmds.insert_resource(Heartbeat {
+        notes,
+        next: 0,
+        last: None,
+    });
+}
+
+/// One note per formation step, so the tempo follows the march, skipping steps that come too soon
+/// after the last note.
+fn play(
+    mut cmds: Commands,
+    time: Res<Time>,
+    mut stepped: EventReader<FormationStepped>,
+    mut heartbeat: ResMut<Heartbeat>,
+) {
+    stepped.clear();
+
+    let now = time.elapsed();
+    if heartbeat
+        .last
+        .is_some_and(|last| now.saturating_sub(last) < MIN_INTERVAL)
+    {
+        return;
+    }
+
+    let note = heartbeat.notes[heartbeat.next].clone();
+    heartbeat.next = (heartbeat.next + 1) % NOTES.len();
+    heartbeat.last = Some(now);
+
+    cmds.spawn((AudioPlayer(note), PlaybackSettings::DESPAWN));
+}
@@ -1,16 +1,26 @@
-use crate::{constants::*, player::Lives, score::Score, GameState};
+use crate::{
+    constants::*,
+    player::Lives,
+    score::{HighScore, Score},
+    turns::Players,
+    GameState, InGame, NewGame,
+};
 use bevy::{prelude::*, text::FontSmoothing, window::WindowResized};
 
 pub struct HudPlugin;
 
 impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), (setup, init_scale).chain())
+        app.add_systems(NewGame, (setup, init_scale).chain())
             .add_systems(
                 Update,
                 (
                     update_scale,
-                    update_score.run_if(resource_exists_and_changed::<Score>),
+                    update_score.run_if(
+                        resource_exists_and_changed::<Score>
+                            .or(resource_exists_and_changed::<Players>),
+                    ),
+                    update_high_score.run_if(resource_exists_and_changed::<HighScore>),
                     update_lives.run_if(resource_exists_and_changed::<Lives>),
                 )
                     .run_if(in_state(GameState::Playing)),
@@ -18,8 +28,12 @@ impl Plugin for HudPlugin {
     }
 }
 
+/// Score of the player with this index.
 #[derive(Component)]
-struct ScoreIndicator;
+struct ScoreIndicator(usize);
+
+#[derive(Component)]
+struct HighScoreIndicator;
 
 #[derive(Component)]
 struct LivesIndicator;
@@ -27,7 +41,12 @@ struct LivesIndicator;
 #[derive(Component)]
 struct LivesImagesIndicator;
 
-fn setup(mut cmds: Commands, asset_server: Res<AssetServer>) {
+fn setup(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    high_score: Res<HighScore>,
+    players: Res<Players>,
+) {
     use FlexDirection::*;
     use JustifyContent::*;
     use Val::*;
@@ -39,15 +58,18 @@ fn setup(mut cmds: Commands, asset_server: Res<AssetServer>) {
     };
     let color: TextColor = Color::srgb(0.7, 0.7, 0.7).into();
 
-    cmds.spawn(Node {
-        width: Percent(100.),
-        height: Percent(100.),
-        flex_direction: Column,
-        align_items: AlignItems::Stretch,
-        justify_content: SpaceBetween,
-        padding: UiRect::all(Px(5.)),
-        ..default()
-    })
+    cmds.spawn((
+        Node {
+            width: Percent(100.),
+            height: Percent(100.),
+            flex_direction: Column,
+            align_items: AlignItems::Stretch,
+            justify_content: SpaceBetween,
+            padding: UiRect::all(Px(5.)),
+            ..default()
+        },
+        StateScoped(InGame),
+    ))
     .with_children(|parent| {
         // top text
         parent
@@ -80,7 +102,7 @@ fn setup(mut cmds: Commands, asset_server: Res<AssetServer>) {
                             .with_children(|parent| {
                                 parent.spawn((Text::new("Score <1>"), font.clone(), color));
                                 parent.spawn((
-                                    ScoreIndicator,
+                                    ScoreIndicator(0),
                                     Text::new(" 0000"),
                                     font.clone(),
                                     color,
@@ -96,7 +118,12 @@ fn setup(mut cmds: Commands, asset_server: Res<AssetServer>) {
                             })
                             .with_children(|parent| {
                                 parent.spawn((Text::new("Hi-Score"), font.clone(), color));
-                                parent.spawn((Text::new(" 0000"), font.clone(), color));
+                                parent.spawn((
+                                    HighScoreIndicator,
+                                    Text::new(format_high_score(&high_score)),
+                                    font.clone(),
+                                    color,
+                                ));
                             });
 
                         parent
@@ -108,7 +135,12 @@ fn setup(mut cmds: Commands, asset_server: Res<AssetServer>) {
                             })
                             .with_children(|parent| {
                                 parent.spawn((Text::new("Score <2>"), font.clone(), color));
-                                parent.spawn((Text::new(""), font.clone(), color));
+                                parent.spawn((
+                                    ScoreIndicator(1),
+                                    Text::new(if players.count > 1 { " 0000" } else { "" }),
+                                    font.clone(),
+                                    color,
+                                ));
                             });
                         // hi-score
                     });
@@ -146,17 +178,7 @@ fn setup(mut cmds: Commands, asset_server: Res<AssetServer>) {
                                         LivesImagesIndicator,
                                     ))
                                     .with_children(|parent| {
-                                        let mut image =
-                                            ImageNode::new(asset_server.load("sprites/player.png"));
-                                        image.color = Color::srgb(0., 1., 0.);
-                                        let node = Node {
-                                            width: Px(PLAYER_SIZE.x),
-                                            height: Px(PLAYER_SIZE.y),
-                                            margin: UiRect::new(Px(5.), Px(0.), Px(0.), Px(0.)),
-                                            ..default()
-                                        };
-                                        parent.spawn((node.clone(), image.clone()));
-                                        parent.spawn((node.clone(), image.clone()));
+                                        spawn_life_images(parent, &asset_server, PLAYER_LIVES);
                                     });
                             });
                         parent.spawn((Text::new("Credit 00"), font.clone(), color));
@@ -188,24 +210,65 @@ fn set_scale(ui_scale: &mut UiScale, window: &Window) {
     ui_scale.0 = scale.x.min(scale.y);
 }
 
-fn update_score(score: Res<Score>, indicator: Single<&mut Text, With<ScoreIndicator>>) {
+/// The current player's score is live in `Score`, the waiting player's is stashed in `Players`.
+fn update_score(
+    score: Res<Score>,
+    players: Res<Players>,
+    mut indicators: Query<(&mut Text, &ScoreIndicator)>,
+) {
+    for (mut text, indicator) in indicators.iter_mut() {
+        let score = if indicator.0 == players.current {
+            Some(score.0)
+        } else {
+            players.stashed_score(indicator.0)
+        };
+
+        *text = Text::new(score.map(|s| format!(" {}", s)).unwrap_or_default());
+    }
+}
+
+fn update_high_score(
+    high_score: Res<HighScore>,
+    indicator: Single<&mut Text, With<HighScoreIndicator>>,
+) {
     let mut indicator = indicator.into_inner();
-    *indicator = Text::new(format!(" {}", score.0));
+    *indicator = Text::new(format_high_score(&high_score));
+}
+
+fn format_high_score(high_score: &HighScore) -> String {
+    format!(" {:04}", high_score.0)
 }
 
 fn update_lives(
     mut cmds: Commands,
     lives: Res<Lives>,
+    asset_server: Res<AssetServer>,
     indicator: Single<&mut Text, With<LivesIndicator>>,
-    images_indicator: Single<(Entity, Option<&Children>), With<LivesImagesIndicator>>,
+    images_indicator: Single<Entity, With<LivesImagesIndicator>>,
 ) {
     let mut indicator = indicator.into_inner();
     *indicator = Text::new(format!(" {}", lives.0));
 
-    let (_parent, children) = images_indicator.into_inner();
-    if let Some(children) = children {
-        if let Some(&last_child) = children.iter().last() {
-            cmds.entity(last_child).despawn();
-        }
+    // rebuild rather than remove one, as lives also change when players swap turns
+    cmds.entity(images_indicator.into_inner())
+        .despawn_descendants()
+        .with_children(|parent| {
+            spawn_life_images(parent, &asset_server, lives.0);
+        });
+}
+
+/// One cannon per life in reserve, i.e. not counting the one in play.
+fn spawn_life_images(parent: &mut ChildBuilder, asset_server: &AssetServer, lives: usize) {
+    let mut image = ImageNode::new(asset_server.load("sprites/player.png"));
+    image.color = Color::srgb(0., 1., 0.);
+    let node = Node {
+        width: Val::Px(PLAYER_SIZE.x),
+        height: Val::Px(PLAYER_SIZE.y),
+        margin: UiRect::new(Val::Px(5.), Val::Px(0.), Val::Px(0.), Val::Px(0.)),
+        ..default()
+    };
+
+    for _ in 1..lives {
+        parent.spawn((node.clone(), image.clone()));
     }
 }
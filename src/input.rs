@@ -0,0 +1,399 @@
+use crate::{
+    player::{PlayerInput, ReadInput},
+    storage,
+    touch::TouchButton,
+    turns::Players,
+};
+use bevy::{
+    ecs::system::SystemParam,
+    input::{gamepad::GamepadConnectionEvent, InputSystem},
+    prelude::*,
+    reflect::{DynamicEnum, Enum, TypeInfo, Typed, VariantInfo},
+};
+
+/// Keyboard, gamepad and touch input, through rebindable actions. Each player gets the pad they
+/// plugged in; with a single pad, both players share it.
+pub struct InputPlugin;
+
+const BINDINGS_KEY: &str = "bindings";
+
+/// Stick deflection below which the cannon doesn't move, so worn sticks don't drift.
+const STICK_DEAD_ZONE: f32 = 0.25;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Pads::default())
+            .add_systems(Startup, load_bindings)
+            .add_systems(PreUpdate, assign_pads)
+            // every frame, as a frame may run no fixed tick and a press would be lost in between
+            .add_systems(PreUpdate, read_input.in_set(ReadInput).after(InputSystem));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    /// Steps up through menus.
+    MoveUp,
+    /// Steps down through menus.
+    MoveDown,
+    Fire,
+    Start,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::Fire,
+        Action::Start,
+        Action::Pause,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::Fire => "Fire",
+            Action::Start => "Start",
+            Action::Pause => "Pause",
+        }
+    }
+
+    /// The on-screen button for the action, which can't be rebound.
+    fn touch_button(self) -> Option<TouchButton> {
+        match self {
+            Action::MoveLeft => Some(TouchButton::Left),
+            Action::MoveRight => Some(TouchButton::Right),
+            Action::Fire => Some(TouchButton::Fire),
+            Action::Pause => Some(TouchButton::Pause),
+            Action::MoveUp | Action::MoveDown | Action::Start => None,
+        }
+    }
+
+    fn index(self) -> usize {
+        Action::ALL
+            .iter()
+            .position(|action| *action == self)
+            .unwrap()
+    }
+}
+
+/// The keys and pad button that trigger an action.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Binding {
+    pub keys: Vec<KeyCode>,
+    pub button: Option<GamepadButton>,
+}
+
+impl Binding {
+    fn new(keys: &[KeyCode], button: GamepadButton) -> Self {
+        Self {
+            keys: keys.to_vec(),
+            button: Some(button),
+        }
+    }
+}
+
+/// What triggers each action, saved between sessions.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct Bindings([Binding; 7]);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self([
+            Binding::new(
+                &[KeyCode::ArrowLeft, KeyCode::KeyA],
+                GamepadButton::DPadLeft,
+            ),
+            Binding::new(
+                &[KeyCode::ArrowRight, KeyCode::KeyD],
+                GamepadButton::DPadRight,
+            ),
+            Binding::new(&[KeyCode::ArrowUp, KeyCode::KeyW], GamepadButton::DPadUp),
+            Binding::new(
+                &[KeyCode::ArrowDown, KeyCode::KeyS],
+                GamepadButton::DPadDown,
+            ),
+            Binding::new(&[KeyCode::Space], GamepadButton::South),
+            Binding::new(&[KeyCode::Enter, KeyCode::Digit1], GamepadButton::Start),
+            Binding::new(&[KeyCode::Escape, KeyCode::KeyP], GamepadButton::Select),
+        ])
+    }
+}
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> &Binding {
+        &self.0[action.index()]
+    }
+
+    pub fn get_mut(&mut self, action: Action) -> &mut Binding {
+        &mut self.0[action.index()]
+    }
+
+    pub fn save(&self) {
+        storage::save(BINDINGS_KEY, &self.to_string());
+    }
+
+    /// One line per action, e.g. `Fire: Space KeyX; South`. Keys and buttons go by the name of
+    /// their `KeyCode` and `GamepadButton` variant.
+    fn parse(text: &str) -> Option<Self> {
+        let mut bindings = Self::default();
+
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let (action, rest) = line.split_once(':')?;
+            let (keys, button) = rest.split_once(';')?;
+            let action = *Action::ALL
+                .iter()
+                .find(|candidate| format!("{candidate:?}") == action.trim())?;
+
+            let binding = bindings.get_mut(action);
+            binding.keys = keys
+                .split_whitespace()
+                .map(from_variant_name)
+                .collect::<Option<_>>()?;
+            binding.button = match button.trim() {
+                "" => None,
+                name => Some(from_variant_name(name)?),
+            };
+        }
+
+        Some(bindings)
+    }
+}
+
+impl std::fmt::Display for Bindings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for action in Action::ALL {
+            let binding = self.get(action);
+            write!(f, "{action:?}:")?;
+            for key in &binding.keys {
+                write!(f, " {}", key.variant_name())?;
+            }
+            write!(f, ";")?;
+            if let Some(button) = binding.button {
+                write!(f, " {}", button.variant_name())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Looks up a unit variant of a reflected enum by name.
+fn from_variant_name<T: FromReflect + Typed>(name: &str) -> Option<T> {
+    // `from_reflect` panics on names that aren't variants of the enum
+    let TypeInfo::Enum(info) = T::type_info() else {
+        return None;
+    };
+    if !matches!(info.variant(name)?, VariantInfo::Unit(_)) {
+        return None;
+    }
+
+    T::from_reflect(&DynamicEnum::new(name, ()))
+}
+
+/// Name of a key, for the controls screen.
+pub fn key_name(key: KeyCode) -> String {
+    let name = key.variant_name();
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(name)
+        .to_string()
+}
+
+/// Name of a pad button, for the controls screen.
+pub fn button_name(button: GamepadButton) -> String {
+    button.variant_name().to_string()
+}
+
+/// The pad of each player, in the order they were connected.
+#[derive(Resource, Default, Debug)]
+pub struct Pads(pub [Option<Entity>; 2]);
+
+impl Pads {
+    /// The pad controlling the given player's cannon: their own, or else the first one still
+    /// connected, which both players share.
+    pub fn for_player(&self, player: usize) -> Option<Entity> {
+        self.0[player].or_else(|| self.0.iter().flatten().next().copied())
+    }
+}
+
+/// Reads actions from the keyboard, the touch controls and any pad, or only the pad set with
+/// `only_gamepad`.
+#[derive(SystemParam)]
+pub struct Actions<'w, 's> {
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    gamepads: Query<'w, 's, (Entity, &'static Gamepad)>,
+    touch: Option<Res<'w, ButtonInput<TouchButton>>>,
+    bindings: Res<'w, Bindings>,
+    gamepad_filter: Local<'s, GamepadFilter>,
+}
+
+#[derive(Default)]
+enum GamepadFilter {
+    #[default]
+    Any,
+    /// This pad only, or none at all.
+    Only(Option<Entity>),
+}
+
+impl Actions<'_, '_> {
+    /// From now on, only listens to `gamepad`, or to no pad at all if there is none.
+    pub fn only_gamepad(&mut self, gamepad: Option<Entity>) {
+        *self.gamepad_filter = GamepadFilter::Only(gamepad);
+    }
+
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    /// The pads listened to.
+    fn gamepads(&self) -> impl Iterator<Item = &Gamepad> {
+        self.gamepads
+            .iter()
+            .filter(|(entity, _)| match *self.gamepad_filter {
+                GamepadFilter::Any => true,
+                GamepadFilter::Only(only) => only == Some(*entity),
+            })
+            .map(|(_, gamepad)| gamepad)
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        let binding = self.bindings.get(action);
+        self.keyboard.any_pressed(binding.keys.iter().copied())
+            || binding
+                .button
+                .is_some_and(|button| self.gamepads().any(|gamepad| gamepad.pressed(button)))
+            || touched(&self.touch, action, ButtonInput::pressed)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        let binding = self.bindings.get(action);
+        self.keyboard.any_just_pressed(binding.keys.iter().copied())
+            || binding
+                .button
+                .is_some_and(|button| self.gamepads().any(|gamepad| gamepad.just_pressed(button)))
+            || touched(&self.touch, action, ButtonInput::just_pressed)
+    }
+
+    /// From -1 (full left) to 1 (full right), from the move actions and the left stick.
+    pub fn movement(&self) -> f32 {
+        // the stick pushed furthest, past the dead zone
+        let mut movement = self
+            .gamepads()
+            .map(|gamepad| gamepad.left_stick().x)
+            .filter(|stick| stick.abs() >= STICK_DEAD_ZONE)
+            .map(|stick| stick.signum() * (stick.abs() - STICK_DEAD_ZONE) / (1. - STICK_DEAD_ZONE))
+            .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+            .unwrap_or(0.);
+        if self.pressed(Action::MoveLeft) {
+            movement -= 1.0;
+        }
+        if self.pressed(Action::MoveRight) {
+            movement += 1.0;
+        }
+
+        movement.clamp(-1., 1.)
+    }
+}
+
+fn touched(
+    touch: &Option<Res<ButtonInput<TouchButton>>>,
+    action: Action,
+    check: impl Fn(&ButtonInput<TouchButton>, TouchButton) -> bool,
+) -> bool {
+    touch
+        .as_ref()
+        .zip(action.touch_button())
+        .is_some_and(|(touch, button)| check(touch, button))
+}
+
+fn load_bindings(mut cmds: Commands) {
+    let bindings = storage::load(BINDINGS_KEY)
+        .and_then(|text| Bindings::parse(&text))
+        .unwrap_or_default();
+
+    cmds.insert_resource(bindings);
+}
+
+fn assign_pads(mut pads: ResMut<Pads>, mut connections: EventReader<GamepadConnectionEvent>) {
+    for event in connections.read() {
+        if event.connected() {
+            if let Some(slot) = pads.0.iter_mut().find(|slot| slot.is_none()) {
+                *slot = Some(event.gamepad);
+            }
+        } else {
+            for slot in pads.0.iter_mut() {
+                if *slot == Some(event.gamepad) {
+                    *slot = None;
+                }
+            }
+        }
+    }
+}
+
+/// The keyboard, the touch controls and the current player's pad drive the cannon. Fire stays
+/// latched until a tick has seen it.
+fn read_input(
+    mut actions: Actions,
+    pads: Res<Pads>,
+    players: Res<Players>,
+    mut input: ResMut<PlayerInput>,
+) {
+    actions.only_gamepad(pads.for_player(players.current));
+
+    input.movement = actions.movement();
+    input.fire |= actions.just_pressed(Action::Fire);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::input::gamepad::GamepadConnection;
+
+    fn plug(app: &mut App, gamepad: Entity, connected: bool) {
+        let connection = if connected {
+            GamepadConnection::Connected {
+                name: "pad".into(),
+                vendor_id: None,
+                product_id: None,
+            }
+        } else {
+            GamepadConnection::Disconnected
+        };
+        app.world_mut()
+            .send_event(GamepadConnectionEvent::new(gamepad, connection));
+        app.update();
+    }
+
+    #[test]
+    fn unplugging_the_first_pad_hands_the_other_one_to_both_players() {
+        let mut app = App::new();
+        app.add_event::<GamepadConnectionEvent>()
+            .init_resource::<Pads>()
+            .add_systems(Update, assign_pads);
+
+        let first = Entity::from_raw(1);
+        let second = Entity::from_raw(2);
+        plug(&mut app, first, true);
+        plug(&mut app, second, true);
+        let pads = app.world().resource::<Pads>();
+        assert_eq!(pads.for_player(0), Some(first));
+        assert_eq!(pads.for_player(1), Some(second));
+
+        plug(&mut app, first, false);
+        let pads = app.world().resource::<Pads>();
+        assert_eq!(pads.for_player(0), Some(second));
+        assert_eq!(pads.for_player(1), Some(second));
+
+        plug(&mut app, second, false);
+        assert_eq!(app.world().resource::<Pads>().for_player(0), None);
+    }
+}
@@ -0,0 +1,217 @@
+//! Space Invaders, split between the rules of the game and how they are presented.
+//!
+//! [`GameplayPlugins`] holds the rules alone: no window, rendering, audio or asset loading, so it
+//! runs under `MinimalPlugins` for tests and servers, driven through [`player::PlayerInput`];
+//! [`headless_app`] sets one up with a game about to start.
+//! [`PresentationPlugins`] adds everything that is drawn, heard, typed or saved on top of it.
+//!
+//! Games can be recorded and played back tick-for-tick, see [`replay`].
+
+pub mod camera;
+pub mod constants;
+pub mod enemy;
+pub mod game_over;
+pub mod heartbeat;
+pub mod hud;
+pub mod input;
+pub mod menu;
+pub mod pause;
+pub mod player;
+pub mod replay;
+pub mod rng;
+pub mod score;
+pub mod shield;
+pub mod shots;
+pub mod storage;
+pub mod synth;
+pub mod touch;
+pub mod turns;
+pub mod ufo;
+pub mod wall;
+pub mod wave;
+
+use std::time::Duration;
+
+use bevy::{
+    app::PluginGroupBuilder,
+    ecs::schedule::ScheduleLabel,
+    prelude::*,
+    state::{app::StatesPlugin, state::StateTransition},
+    time::TimeUpdateStrategy,
+};
+
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GameState {
+    #[default]
+    MainMenu,
+    Playing,
+    LevelTransition,
+    TurnChange,
+    GameOver,
+    /// Passed through on the way from a game to a new one with the same players, so the old one is
+    /// cleared away and the new one set up as if it had started from the menu.
+    Restarting,
+}
+
+/// What is going on while `GameState::Playing`.
+#[derive(SubStates, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[source(GameState = GameState::Playing)]
+pub enum PlayState {
+    #[default]
+    Running,
+    /// The cannon was hit: invaders freeze while it explodes.
+    PlayerDying,
+}
+
+/// A game is going on, from the moment it leaves the menu until it returns there or restarts.
+/// Everything a game spawns is `StateScoped` to it, so nothing is left over for the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InGame;
+
+impl ComputedStates for InGame {
+    type SourceStates = GameState;
+
+    fn compute(state: GameState) -> Option<Self> {
+        (!matches!(state, GameState::MainMenu | GameState::Restarting)).then_some(InGame)
+    }
+}
+
+/// Whether the game is paused, which only makes sense while one is going on. The game clock
+/// stands still while `Paused`, so no fixed tick runs and no timer advances.
+#[derive(SubStates, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[source(GameState = GameState::Playing | GameState::LevelTransition | GameState::TurnChange)]
+pub enum PauseState {
+    #[default]
+    Unpaused,
+    Paused,
+}
+
+/// Runs once when a new game starts, from the menu or by restarting, whereas
+/// `OnEnter(GameState::Playing)` also runs at the start of every wave and every turn.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NewGame;
+
+/// The phases of a fixed tick, in order. A rule that depends on what another one changes goes in
+/// a later phase, so every tick plays out the same way whatever order the scheduler picks.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TickSet {
+    /// The cannon, invaders, shots and mystery ship move.
+    Move,
+    /// Everyone fires from where they now are.
+    Fire,
+    /// What collided gets hit.
+    Collide,
+}
+
+/// The rules of the game. Add `MinimalPlugins` alongside to run it headless.
+pub struct GameplayPlugins;
+
+impl PluginGroup for GameplayPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(StatePlugin)
+            .add(rng::RngPlugin)
+            .add(wall::WallPlugin)
+            .add(player::PlayerPlugin)
+            .add(enemy::EnemyPlugin)
+            .add(shield::ShieldPlugin)
+            .add(shots::ShotPlugin)
+            .add(score::ScorePlugin)
+            .add(wave::WavePlugin)
+            .add(ufo::UfoPlugin)
+            .add(turns::TurnsPlugin)
+            .add(replay::ReplayPlugin)
+    }
+}
+
+/// The rules under `MinimalPlugins`, with a game for `players` that starts on the first update.
+/// Every update advances the game clock by exactly one fixed tick, however fast it runs; insert
+/// another `TimeUpdateStrategy` to change that.
+pub fn headless_app(players: usize) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, GameplayPlugins))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1. / 60.,
+        )))
+        .insert_resource(turns::Players::new(players));
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::TurnChange);
+    app
+}
+
+/// Sprites, sounds, screens, input devices and persistence. Needs `DefaultPlugins` and
+/// `GameplayPlugins`.
+pub struct PresentationPlugins;
+
+impl PluginGroup for PresentationPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(synth::SynthPlugin)
+            .add(camera::CameraPlugin)
+            .add(input::InputPlugin)
+            .add(touch::TouchPlugin)
+            .add(player::PlayerPresentationPlugin)
+            .add(enemy::EnemyPresentationPlugin)
+            .add(shield::ShieldPresentationPlugin)
+            .add(shots::ShotPresentationPlugin)
+            .add(score::HighScoreStoragePlugin)
+            .add(wave::WavePresentationPlugin)
+            .add(ufo::UfoPresentationPlugin)
+            .add(turns::TurnsPresentationPlugin)
+            .add(hud::HudPlugin)
+            .add(menu::MenuPlugin)
+            .add(game_over::GameOverPlugin)
+            .add(pause::PausePlugin)
+            .add(heartbeat::HeartbeatPlugin)
+    }
+}
+
+struct StatePlugin;
+
+impl Plugin for StatePlugin {
+    fn build(&self, app: &mut App) {
+        // `DefaultPlugins` already has it, `MinimalPlugins` doesn't
+        if !app.is_plugin_added::<StatesPlugin>() {
+            app.add_plugins(StatesPlugin);
+        }
+
+        app.init_state::<GameState>()
+            .add_sub_state::<PlayState>()
+            .add_sub_state::<PauseState>()
+            .add_computed_state::<InGame>()
+            .enable_state_scoped_entities::<GameState>()
+            .enable_state_scoped_entities::<PauseState>()
+            .enable_state_scoped_entities::<InGame>()
+            .insert_resource(Time::<Fixed>::from_hz(60.0))
+            .configure_sets(
+                FixedUpdate,
+                (TickSet::Move, TickSet::Fire, TickSet::Collide).chain(),
+            )
+            .init_schedule(NewGame)
+            .add_systems(OnExit(GameState::MainMenu), start_new_game)
+            .add_systems(OnExit(GameState::Restarting), start_new_game)
+            .add_systems(FixedFirst, apply_state_transitions)
+            .add_systems(OnEnter(PauseState::Paused), stop_clock)
+            .add_systems(OnExit(PauseState::Paused), start_clock);
+    }
+}
+
+fn start_new_game(world: &mut World) {
+    world.run_schedule(NewGame);
+}
+
+fn stop_clock(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn start_clock(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+/// States normally change once per frame, and a frame may run any number of fixed ticks. Changing
+/// them at the start of every tick as well keeps the rules tick-for-tick identical whatever the
+/// frame rate, which replays rely on.
+fn apply_state_transitions(world: &mut World) {
+    world.run_schedule(StateTransition);
+}
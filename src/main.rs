@@ -1,53 +1,105 @@
-mod camera;
-mod constants;
-mod enemy;
-mod game_over;
-mod hud;
-mod menu;
-mod player;
-mod score;
-mod shield;
-mod shots;
-mod wall;
-
 use bevy::prelude::*;
-use constants::*;
-
-#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
-pub enum GameState {
-    #[default]
-    MainMenu,
-    Playing,
-    GameOver,
-}
+use bevy_space_invaders::{
+    constants::*,
+    replay::{Playback, Recorder, Replay},
+    rng::GameRng,
+    shots::{CollisionMatrix, CollisionMode},
+    GameplayPlugins, PresentationPlugins,
+};
 
 fn main() {
-    App::new()
-        .add_plugins(
-            DefaultPlugins
-                .build()
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        canvas: Some("#canvas".into()),
-                        title: "Space Invaders".into(),
-                        ..default()
-                    }),
+    let mut app = App::new();
+
+    #[cfg(target_arch = "wasm32")]
+    if let Some(seed) = query_seed() {
+        app.insert_resource(GameRng::from_seed(seed));
+    }
+
+    // `--record <file>` saves the next game as a replay, `--replay <file>` plays one back,
+    // `--seed <n>` makes every game play with the same randomness, and `--pixel-perfect` collides
+    // with the sprites' pixels rather than their boxes, unless a replay says otherwise.
+    // `--collisions <file>` loads which layers collide from a file, in the format of
+    // `CollisionMatrix::parse`
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--pixel-perfect" {
+            app.insert_resource(CollisionMode::PixelPerfect);
+            continue;
+        }
+
+        match (arg.as_str(), args.next()) {
+            ("--record", Some(path)) => {
+                app.insert_resource(Recorder::new(Some(path.into())));
+            }
+            ("--seed", Some(seed)) => match seed.parse() {
+                Ok(seed) => {
+                    app.insert_resource(GameRng::from_seed(seed));
+                }
+                Err(err) => {
+                    eprintln!("Invalid seed {seed}: {err}");
+                    std::process::exit(1);
+                }
+            },
+            ("--replay", Some(path)) => match Replay::load(&path) {
+                Ok(replay) => {
+                    app.insert_resource(Playback::new(replay));
+                }
+                Err(err) => {
+                    eprintln!("Failed to load replay {path}: {err}");
+                    std::process::exit(1);
+                }
+            },
+            ("--collisions", Some(path)) => match CollisionMatrix::load(&path) {
+                Ok(matrix) => {
+                    app.insert_resource(matrix);
+                }
+                Err(err) => {
+                    eprintln!("Failed to load collisions {path}: {err}");
+                    std::process::exit(1);
+                }
+            },
+            _ => {
+                eprintln!(concat!(
+                    "Usage: bevy-space-invaders [--seed <n>] [--pixel-perfect] ",
+                    "[--collisions <file>] [--record <file> | --replay <file>]",
+                ));
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // a replay only plays back the same with the collisions it was recorded with
+    if let Some(playback) = app.world().get_resource::<Playback>() {
+        let matrix = app.world().get_resource::<CollisionMatrix>().cloned();
+        if !playback.replay.recorded_with(&matrix.unwrap_or_default()) {
+            eprintln!("The replay was recorded with other collisions, pass the same --collisions");
+            std::process::exit(1);
+        }
+    }
+
+    app.add_plugins(
+        DefaultPlugins
+            .build()
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    canvas: Some("#canvas".into()),
+                    title: "Space Invaders".into(),
                     ..default()
-                })
-                .set(ImagePlugin::default_nearest()),
-        )
-        .init_state::<GameState>()
-        .insert_resource(ClearColor(BG_COLOR))
-        .insert_resource(Time::<Fixed>::from_hz(60.0))
-        .add_plugins(wall::WallPlugin)
-        .add_plugins(player::PlayerPlugin)
-        .add_plugins(enemy::EnemyPlugin)
-        .add_plugins(shield::ShieldPlugin)
-        .add_plugins(shots::ShotPlugin)
-        .add_plugins(hud::HudPlugin)
-        .add_plugins(camera::CameraPlugin)
-        .add_plugins(score::ScorePlugin)
-        .add_plugins(menu::MenuPlugin)
-        .add_plugins(game_over::GameOverPlugin)
-        .run();
+                }),
+                ..default()
+            })
+            .set(ImagePlugin::default_nearest()),
+    )
+    .insert_resource(ClearColor(BG_COLOR))
+    .add_plugins(GameplayPlugins)
+    .add_plugins(PresentationPlugins)
+    .run();
+}
+
+/// The `seed` parameter of the page's URL, e.g. `index.html?seed=42`.
+#[cfg(target_arch = "wasm32")]
+fn query_seed() -> Option<u64> {
+    let search = web_sys::window()?.location().search().ok()?;
+    let params = web_sys::UrlSearchParams::new_with_str(&search).ok()?;
+    params.get("seed")?.parse().ok()
 }
@@ -1,29 +1,78 @@
 use bevy::{prelude::*, text::FontSmoothing};
 
-use crate::GameState;
+use crate::{
+    input::{self, Action, Actions, Bindings},
+    turns::Players,
+    GameState,
+};
 
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::MainMenu), setup)
-            .add_systems(FixedUpdate, update.run_if(in_state(GameState::MainMenu)))
+            .add_systems(
+                Update,
+                (
+                    update,
+                    render.run_if(
+                        resource_exists_and_changed::<MenuCursor>
+                            .or(resource_exists_and_changed::<Bindings>),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::MainMenu)),
+            )
             .add_systems(OnExit(GameState::MainMenu), cleanup);
     }
 }
 
+const MAIN_ENTRIES: [&str; 3] = ["1 PLAYER", "2 PLAYERS", "CONTROLS"];
+
 #[derive(Component)]
 struct Menu;
 
+#[derive(Resource)]
+struct MenuFont(TextFont);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Screen {
+    #[default]
+    Main,
+    /// One entry per action, then "BACK".
+    Controls,
+}
+
+impl Screen {
+    fn len(self) -> usize {
+        match self {
+            Screen::Main => MAIN_ENTRIES.len(),
+            Screen::Controls => Action::ALL.len() + 1,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct MenuCursor {
+    screen: Screen,
+    selected: usize,
+    /// The action waiting for a new key or button.
+    rebinding: Option<Action>,
+    /// The action that already has the last key or button pressed while rebinding.
+    taken_by: Option<Action>,
+}
+
 fn setup(mut cmds: Commands, asset_server: Res<AssetServer>) {
-    let font = TextFont {
+    cmds.insert_resource(MenuFont(TextFont {
         font: asset_server.load("font.ttf"),
         font_size: 14.0,
         font_smoothing: FontSmoothing::None,
-    };
+    }));
+    cmds.insert_resource(MenuCursor::default());
 
     cmds.spawn((
         Menu,
+        StateScoped(GameState::MainMenu),
         Node {
             width: Val::Percent(100.),
             height: Val::Percent(100.),
@@ -32,20 +81,170 @@ fn setup(mut cmds: Commands, asset_server: Res<AssetServer>) {
             justify_content: JustifyContent::Center,
             ..default()
         },
-    ))
-    .with_children(|parent| {
-        parent.spawn((Text::new("Press SPACE to start"), font.clone()));
-    });
+    ));
 }
 
-fn update(keyboard: Res<ButtonInput<KeyCode>>, mut state: ResMut<NextState<GameState>>) {
-    if keyboard.just_pressed(KeyCode::Space) {
-        state.set(GameState::Playing);
+fn update(
+    mut cmds: Commands,
+    actions: Actions,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut cursor: ResMut<MenuCursor>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    if let Some(action) = cursor.rebinding {
+        if actions.just_pressed(Action::Pause) {
+            cursor.rebinding = None;
+            cursor.taken_by = None;
+            return;
+        }
+
+        // keys and buttons without a name of their own can't be saved
+        let key = keyboard
+            .get_just_pressed()
+            .find(|key| !matches!(key, KeyCode::Unidentified(_)));
+        let button = gamepads.iter().find_map(|gamepad| {
+            gamepad
+                .get_just_pressed()
+                .find(|button| !matches!(button, GamepadButton::Other(_)))
+        });
+
+        if key.is_none() && button.is_none() {
+            return;
+        }
+
+        // a key or button triggers a single action
+        let mut bindings = actions.bindings().clone();
+        let taken_by = Action::ALL.into_iter().find(|other| {
+            let binding = bindings.get(*other);
+            *other != action
+                && (key.is_some_and(|key| binding.keys.contains(key))
+                    || button.is_some_and(|button| binding.button == Some(*button)))
+        });
+        if taken_by.is_some() {
+            cursor.taken_by = taken_by;
+            return;
+        }
+
+        let binding = bindings.get_mut(action);
+        if let Some(key) = key {
+            // pressing one of the action's keys again removes it, unless it's the last one
+            match binding.keys.iter().position(|bound| bound == key) {
+                Some(index) if binding.keys.len() > 1 => {
+                    binding.keys.remove(index);
+                }
+                Some(_) => {}
+                None => binding.keys.push(*key),
+            }
+        }
+        if let Some(button) = button {
+            binding.button = Some(*button);
+        }
+        bindings.save();
+        cmds.insert_resource(bindings);
+        cursor.rebinding = None;
+        cursor.taken_by = None;
+        return;
     }
-}
 
-fn cleanup(mut cmds: Commands, query: Query<Entity, With<Menu>>) {
-    for entity in &query {
-        cmds.entity(entity).despawn_recursive();
+    let len = cursor.screen.len();
+    if actions.just_pressed(Action::MoveUp) {
+        cursor.selected = (cursor.selected + len - 1) % len;
     }
+    if actions.just_pressed(Action::MoveDown) {
+        cursor.selected = (cursor.selected + 1) % len;
+    }
+
+    if !actions.just_pressed(Action::Start) && !actions.just_pressed(Action::Fire) {
+        return;
+    }
+
+    match (cursor.screen, cursor.selected) {
+        (Screen::Main, 0 | 1) => {
+            cmds.insert_resource(Players::new(cursor.selected + 1));
+            state.set(GameState::TurnChange);
+        }
+        (Screen::Main, _) => {
+            cursor.screen = Screen::Controls;
+            cursor.selected = 0;
+        }
+        (Screen::Controls, selected) => match Action::ALL.get(selected) {
+            Some(action) => cursor.rebinding = Some(*action),
+            None => {
+                cursor.screen = Screen::Main;
+                cursor.selected = MAIN_ENTRIES.len() - 1;
+            }
+        },
+    }
+}
+
+fn render(
+    mut cmds: Commands,
+    font: Res<MenuFont>,
+    cursor: Res<MenuCursor>,
+    bindings: Res<Bindings>,
+    menu: Single<Entity, With<Menu>>,
+) {
+    let entries: Vec<String> = match cursor.screen {
+        Screen::Main => MAIN_ENTRIES.iter().map(|entry| entry.to_string()).collect(),
+        Screen::Controls => Action::ALL
+            .iter()
+            .map(|action| {
+                if cursor.rebinding == Some(*action) {
+                    return match cursor.taken_by {
+                        Some(other) => format!("{}: taken by {}", action.name(), other.name()),
+                        None => format!("{}: press a key or button", action.name()),
+                    };
+                }
+
+                let binding = bindings.get(*action);
+                let keys: Vec<String> = binding
+                    .keys
+                    .iter()
+                    .map(|key| input::key_name(*key))
+                    .collect();
+                let button = binding.button.map(input::button_name).unwrap_or_default();
+                format!("{}: {} / {}", action.name(), keys.join(" "), button)
+            })
+            .chain(["BACK".to_string()])
+            .collect(),
+    };
+
+    cmds.entity(*menu)
+        .despawn_descendants()
+        .with_children(|parent| {
+            for (index, entry) in entries.into_iter().enumerate() {
+                let marker = if index == cursor.selected { "> " } else { "  " };
+                parent.spawn((Text::new(format!("{marker}{entry}")), font.0.clone()));
+            }
+
+            let hint = if cursor.rebinding.is_some() {
+                format!(
+                    "a new key adds it, one of its keys removes it, {} cancels",
+                    Action::Pause.name(),
+                )
+            } else {
+                format!(
+                    "{} / {} to choose, {} to select",
+                    Action::MoveUp.name(),
+                    Action::MoveDown.name(),
+                    Action::Start.name(),
+                )
+            };
+            parent.spawn((
+                Text::new(hint.to_uppercase()),
+                TextFont {
+                    font_size: 8.0,
+                    ..font.0.clone()
+                },
+                Node {
+                    margin: UiRect::top(Val::Px(16.)),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn cleanup(mut cmds: Commands) {
+    cmds.remove_resource::<MenuCursor>();
 }
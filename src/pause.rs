@@ -0,0 +1,191 @@
+#[cfg(target_arch = "wasm32")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bevy::{prelude::*, text::FontSmoothing, window::WindowOccluded};
+
+use crate::{
+    input::{Action, Actions},
+    GameState, PauseState,
+};
+
+/// Pausing with Esc, P or Start, and the menu shown while paused.
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(PauseState::Paused), (setup, mute))
+            .add_systems(
+                Update,
+                (
+                    (pause, pause_when_hidden).run_if(in_state(PauseState::Unpaused)),
+                    (update, render.run_if(resource_changed::<PauseCursor>))
+                        .chain()
+                        .run_if(in_state(PauseState::Paused)),
+                ),
+            )
+            .add_systems(OnExit(PauseState::Paused), (cleanup, unmute));
+
+        // browsers don't tell winit when the tab is hidden
+        #[cfg(target_arch = "wasm32")]
+        app.add_systems(Startup, watch_tab_visibility)
+            .add_systems(PreUpdate, pause_when_tab_hidden);
+    }
+}
+
+const ENTRIES: [&str; 3] = ["RESUME", "RESTART", "QUIT TO MENU"];
+
+#[derive(Component)]
+struct PauseEntry(usize);
+
+#[derive(Resource, Default)]
+struct PauseCursor(usize);
+
+fn pause(actions: Actions, mut next_pause_state: ResMut<NextState<PauseState>>) {
+    if actions.just_pressed(Action::Pause) || actions.just_pressed(Action::Start) {
+        next_pause_state.set(PauseState::Paused);
+    }
+}
+
+/// Nobody is watching when the window is minimized or covered.
+fn pause_when_hidden(
+    mut occlusions: EventReader<WindowOccluded>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if occlusions.read().any(|event| event.occluded) {
+        next_pause_state.set(PauseState::Paused);
+    }
+}
+
+/// Set when the browser tab is hidden. Hidden tabs get no frames, so the game only sees it once the
+/// tab is shown again, and pauses before any time has passed.
+#[cfg(target_arch = "wasm32")]
+static TAB_HIDDEN: AtomicBool = AtomicBool::new(false);
+
+#[cfg(target_arch = "wasm32")]
+fn watch_tab_visibility() {
+    use wasm_bindgen::{closure::Closure, JsCast as _};
+
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else {
+        return;
+    };
+
+    let on_change = Closure::<dyn Fn()>::new(|| {
+        let document = web_sys::window().and_then(|window| window.document());
+        if document.is_some_and(|document| document.hidden()) {
+            TAB_HIDDEN.store(true, Ordering::Relaxed);
+        }
+    });
+    if document
+        .add_event_listener_with_callback("visibilitychange", on_change.as_ref().unchecked_ref())
+        .is_err()
+    {
+        warn!("Can't pause when the tab is hidden");
+    }
+    // the listener stays for as long as the page
+    on_change.forget();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn pause_when_tab_hidden(
+    pause_state: Option<Res<State<PauseState>>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    // a tab hidden outside of a game doesn't pause the next one
+    let hidden = TAB_HIDDEN.swap(false, Ordering::Relaxed);
+    if hidden && pause_state.is_some_and(|state| *state.get() == PauseState::Unpaused) {
+        next_pause_state.set(PauseState::Paused);
+    }
+}
+
+fn setup(mut cmds: Commands, asset_server: Res<AssetServer>) {
+    use Val::*;
+
+    let font = TextFont {
+        font: asset_server.load("font.ttf"),
+        font_size: 14.0,
+        font_smoothing: FontSmoothing::None,
+    };
+    cmds.insert_resource(PauseCursor::default());
+
+    cmds.spawn((
+        StateScoped(PauseState::Paused),
+        Node {
+            width: Percent(100.),
+            height: Percent(100.),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Px(4.),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0., 0., 0., 0.8)),
+        GlobalZIndex(1),
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            Text::new("PAUSED"),
+            font.clone(),
+            Node {
+                margin: UiRect::bottom(Px(12.)),
+                ..default()
+            },
+        ));
+        for index in 0..ENTRIES.len() {
+            parent.spawn((PauseEntry(index), Text::default(), font.clone()));
+        }
+    });
+}
+
+fn update(
+    actions: Actions,
+    mut cursor: ResMut<PauseCursor>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if actions.just_pressed(Action::Pause) {
+        next_pause_state.set(PauseState::Unpaused);
+        return;
+    }
+
+    // the touch controls only have left and right, which step through the entries as well
+    if actions.just_pressed(Action::MoveUp) || actions.just_pressed(Action::MoveLeft) {
+        cursor.0 = (cursor.0 + ENTRIES.len() - 1) % ENTRIES.len();
+    }
+    if actions.just_pressed(Action::MoveDown) || actions.just_pressed(Action::MoveRight) {
+        cursor.0 = (cursor.0 + 1) % ENTRIES.len();
+    }
+
+    if !actions.just_pressed(Action::Start) && !actions.just_pressed(Action::Fire) {
+        return;
+    }
+
+    match cursor.0 {
+        0 => next_pause_state.set(PauseState::Unpaused),
+        1 => next_game_state.set(GameState::Restarting),
+        _ => next_game_state.set(GameState::MainMenu),
+    }
+}
+
+fn render(cursor: Res<PauseCursor>, mut entries: Query<(&PauseEntry, &mut Text)>) {
+    for (entry, mut text) in &mut entries {
+        let marker = if entry.0 == cursor.0 { "> " } else { "  " };
+        text.0 = format!("{marker}{}", ENTRIES[entry.0]);
+    }
+}
+
+fn cleanup(mut cmds: Commands) {
+    cmds.remove_resource::<PauseCursor>();
+}
+
+/// Holds the mystery ship's drone and any other sound that's playing.
+fn mute(sinks: Query<&AudioSink>) {
+    for sink in &sinks {
+        sink.pause();
+    }
+}
+
+fn unmute(sinks: Query<&AudioSink>) {
+    for sink in &sinks {
+        sink.play();
+    }
+}
@@ -1,70 +1,245 @@
 use crate::{
     constants::*,
-    shots::{self, Hit, PlayerShot},
-    GameState,
+    shots::{self, Hit, Hitbox, MaskFromSprite, PlayerShot},
+    turns::Players,
+    GameState, InGame, NewGame, PauseState, PlayState, TickSet,
 };
 use bevy::prelude::*;
 
 pub struct PlayerPlugin;
 
+const EXPLOSION_SIZE: Vec2 = Vec2::new(15., 8.);
+const EXPLOSION_FRAME_SECONDS: f32 = 0.1;
+
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), startup)
+        app.insert_resource(PlayerInput::default())
+            .add_systems(NewGame, startup)
+            .add_systems(OnExit(GameState::TurnChange), recenter)
+            .add_systems(OnEnter(PlayState::PlayerDying), start_dying)
+            .add_systems(OnExit(PlayState::PlayerDying), recenter)
+            .configure_sets(PreUpdate, ReadInput)
+            // after the replay has recorded the tick
+            .add_systems(FixedLast, consume_fire)
+            // the press that resumed the game isn't a shot
+            .add_systems(OnExit(PauseState::Paused), consume_fire)
             .add_systems(
                 FixedUpdate,
-                (move_player, player_shoot).run_if(in_state(GameState::Playing)),
+                (
+                    (
+                        move_player.in_set(TickSet::Move),
+                        player_shoot.in_set(TickSet::Fire),
+                        update_invulnerability,
+                    )
+                        .run_if(in_state(PlayState::Running)),
+                    update_death.run_if(in_state(PlayState::PlayerDying)),
+                ),
+            );
+    }
+}
+
+/// The cannon's sprite, explosion animation and blinking.
+pub struct PlayerPresentationPlugin;
+
+impl Plugin for PlayerPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_sprites)
+            .add_observer(add_sprite)
+            .add_systems(OnEnter(PlayState::PlayerDying), explode)
+            .add_systems(OnExit(PlayState::PlayerDying), restore_sprite)
+            .add_systems(
+                Update,
+                (
+                    animate_explosion.run_if(in_state(PlayState::PlayerDying)),
+                    blink_invulnerable,
+                ),
             );
     }
 }
 
-fn startup(mut cmds: Commands, assets: Res<AssetServer>) {
-    cmds.insert_resource(Lives(3));
+/// Systems that fill `PlayerInput` from input devices, in `PreUpdate`.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadInput;
+
+/// What the player wants to do on the next fixed tick. Written by the input plugins, or directly
+/// by whatever drives a headless simulation.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq)]
+pub struct PlayerInput {
+    /// From -1 (full speed left) to 1 (full speed right).
+    pub movement: f32,
+    /// Fire was pressed since the last tick. Has no effect while a shot is in flight.
+    pub fire: bool,
+}
+
+#[derive(Resource)]
+struct PlayerSprites {
+    cannon: Handle<Image>,
+    explosion: Handle<Image>,
+}
+
+#[derive(Resource)]
+struct DeathTimer(Timer);
+
+#[derive(Resource)]
+struct ExplosionFrameTimer(Timer);
+
+/// Shots pass through the cannon for a little while after it respawns.
+#[derive(Component)]
+struct Invulnerable(Timer);
+
+fn startup(mut cmds: Commands) {
+    cmds.insert_resource(Lives(PLAYER_LIVES));
+    cmds.insert_resource(ShotsFired::default());
+    cmds.insert_resource(PlayerInput::default());
 
-    let player_sprite = assets.load("sprites/player.png");
     cmds.spawn((
         Player,
-        Sprite {
-            image: player_sprite,
-            custom_size: Some(PLAYER_SIZE),
-            color: Color::srgb(0., 1., 0.),
-            ..default()
-        },
+        StateScoped(InGame),
         Transform::from_xyz(ARENA_SIZE.x / 2., PLAYER_FLOOR_GAP, 0.0),
-        shots::Collider::player_layer(),
+        Hitbox(PLAYER_SIZE),
+        shots::Collider::new(shots::Layer::PLAYER),
     ))
     .observe(on_hit);
 }
 
-fn on_hit(
-    _trigger: Trigger<Hit>,
+fn on_hit(_trigger: Trigger<Hit>, mut next_play_state: ResMut<NextState<PlayState>>) {
+    next_play_state.set(PlayState::PlayerDying);
+}
+
+fn start_dying(mut cmds: Commands) {
+    cmds.insert_resource(DeathTimer(Timer::from_seconds(
+        PLAYER_DEATH_SECONDS,
+        TimerMode::Once,
+    )));
+}
+
+/// Waits for the explosion to end, then either respawns the cannon or hands over to the next turn
+/// or the game over screen.
+#[allow(clippy::too_many_arguments)]
+fn update_death(
+    mut cmds: Commands,
+    time: Res<Time>,
+    mut timer: ResMut<DeathTimer>,
     mut lives: ResMut<Lives>,
-    mut _cmds: Commands,
+    mut players: ResMut<Players>,
+    player: Single<Entity, With<Player>>,
     mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_play_state: ResMut<NextState<PlayState>>,
 ) {
-    lives.0 -= 1;
-    if lives.0 == 0 {
-        next_game_state.set(GameState::GameOver);
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    lives.0 = lives.0.saturating_sub(1);
+    if let Some(state) = players.after_death(lives.0) {
+        next_game_state.set(state);
+    } else {
+        next_play_state.set(PlayState::Running);
+        cmds.entity(*player)
+            .remove::<shots::Collider>()
+            .insert(Invulnerable(Timer::from_seconds(
+                PLAYER_INVULNERABLE_SECONDS,
+                TimerMode::Once,
+            )));
     }
 }
 
-#[derive(Resource, Default)]
-pub struct Lives(pub usize);
+fn update_invulnerability(
+    mut cmds: Commands,
+    time: Res<Time>,
+    mut player: Query<(Entity, &mut Invulnerable), With<Player>>,
+) {
+    for (entity, mut invulnerable) in player.iter_mut() {
+        if invulnerable.0.tick(time.delta()).finished() {
+            cmds.entity(entity)
+                .remove::<Invulnerable>()
+                .insert(shots::Collider::new(shots::Layer::PLAYER));
+        }
+    }
+}
 
-#[derive(Component)]
-pub struct Player;
+/// Every turn starts with the cannon back in the middle.
+fn recenter(mut player: Single<&mut Transform, With<Player>>) {
+    player.translation.x = ARENA_SIZE.x / 2.;
+}
+
+fn load_sprites(mut cmds: Commands, assets: Res<AssetServer>) {
+    cmds.insert_resource(PlayerSprites {
+        cannon: assets.load("sprites/player.png"),
+        explosion: assets.load("sprites/player-explosion.png"),
+    });
+}
+
+fn add_sprite(trigger: Trigger<OnAdd, Player>, mut cmds: Commands, sprites: Res<PlayerSprites>) {
+    cmds.entity(trigger.entity()).insert((
+        Sprite {
+            image: sprites.cannon.clone(),
+            custom_size: Some(PLAYER_SIZE),
+            color: GREEN,
+            ..default()
+        },
+        MaskFromSprite::default(),
+    ));
+}
 
-fn move_player(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut player: Single<&mut Transform, With<Player>>,
+fn explode(
+    mut cmds: Commands,
+    sprites: Res<PlayerSprites>,
+    player: Single<&mut Sprite, With<Player>>,
 ) {
-    let mut direction = 0.0;
+    cmds.insert_resource(ExplosionFrameTimer(Timer::from_seconds(
+        EXPLOSION_FRAME_SECONDS,
+        TimerMode::Repeating,
+    )));
 
-    if keyboard.pressed(KeyCode::ArrowLeft) || keyboard.pressed(KeyCode::KeyA) {
-        direction -= 1.0;
+    let mut sprite = player.into_inner();
+    sprite.image = sprites.explosion.clone();
+    sprite.custom_size = Some(EXPLOSION_SIZE);
+}
+
+fn animate_explosion(
+    time: Res<Time>,
+    mut timer: ResMut<ExplosionFrameTimer>,
+    mut sprite: Single<&mut Sprite, With<Player>>,
+) {
+    // the explosion sprite has a single frame, mirroring it stands in for the cabinet's second one
+    if timer.0.tick(time.delta()).just_finished() {
+        sprite.flip_x = !sprite.flip_x;
     }
-    if keyboard.pressed(KeyCode::ArrowRight) || keyboard.pressed(KeyCode::KeyD) {
-        direction += 1.0;
+}
+
+fn restore_sprite(sprites: Res<PlayerSprites>, player: Single<&mut Sprite, With<Player>>) {
+    let mut sprite = player.into_inner();
+    sprite.image = sprites.cannon.clone();
+    sprite.custom_size = Some(PLAYER_SIZE);
+    sprite.flip_x = false;
+}
+
+/// The cannon blinks while shots can't hit it.
+fn blink_invulnerable(mut player: Query<(&mut Visibility, Option<&Invulnerable>), With<Player>>) {
+    for (mut visibility, invulnerable) in player.iter_mut() {
+        let blink = invulnerable
+            .is_some_and(|invulnerable| (invulnerable.0.elapsed_secs() * 10.) as u32 % 2 == 1);
+        *visibility = if blink {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
     }
+}
+
+#[derive(Resource, Default)]
+pub struct Lives(pub usize);
+
+/// Number of shots fired this game, which the mystery ship uses for its scoring table.
+#[derive(Resource, Default)]
+pub struct ShotsFired(pub usize);
+
+#[derive(Component)]
+pub struct Player;
+
+fn move_player(input: Res<PlayerInput>, mut player: Single<&mut Transform, With<Player>>) {
+    let direction = input.movement.clamp(-1.0, 1.0);
 
     let new_position = player.translation.x + direction * PLAYER_SPEED;
     let left_bound = PLAYER_SIZE.x / 2.0 + PLAYER_PADDING;
@@ -72,17 +247,22 @@ fn move_player(
     player.translation.x = new_position.clamp(left_bound, right_bound);
 }
 
+/// Fire only counts for the tick that sees it.
+fn consume_fire(mut input: ResMut<PlayerInput>) {
+    input.fire = false;
+}
+
 fn player_shoot(
     has_shot: Query<(), With<PlayerShot>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    input: Res<PlayerInput>,
     player: Single<&mut Transform, With<Player>>,
-    assets: Res<AssetServer>,
+    mut shots_fired: ResMut<ShotsFired>,
     cmds: Commands,
 ) {
-    if has_shot.is_empty() && keyboard.just_pressed(KeyCode::Space) {
+    if has_shot.is_empty() && input.fire {
+        shots_fired.0 += 1;
         shots::spawn_player_shots(
             cmds,
-            assets,
             Vec2::new(
                 player.translation.x,
                 player.translation.y + PLAYER_SIZE.y / 2.,
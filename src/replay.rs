@@ -0,0 +1,508 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    player::{Lives, PlayerInput},
+    rng::{self, GameRng},
+    score::Score,
+    shots::{CollisionMatrix, CollisionMode, Hitbox},
+    turns::Players,
+    GameState, NewGame,
+};
+use bevy::prelude::*;
+
+pub struct ReplayPlugin;
+
+const MAGIC: &[u8; 4] = b"SIRP";
+const VERSION: u16 = 1;
+
+/// A checksum of the game state is stored every this many ticks.
+const CHECKSUM_INTERVAL: usize = 60;
+
+const FIRE: u8 = 0b01;
+const HAS_CHECKSUM: u8 = 0b10;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, start_playback.run_if(resource_exists::<Playback>))
+            .add_systems(
+                NewGame,
+                (
+                    start_recording
+                        .after(rng::reseed)
+                        .run_if(resource_exists::<Recorder>),
+                    rewind.run_if(resource_exists::<Playback>),
+                ),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                stop_recording.run_if(resource_exists::<Recorder>),
+            )
+            // after input devices were read in `PreUpdate`, so playback overrides them
+            .add_systems(
+                FixedPreUpdate,
+                (
+                    quantize_input.run_if(resource_exists::<Recorder>),
+                    play_input.run_if(resource_exists::<Playback>),
+                ),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                (
+                    record.run_if(resource_exists::<Recorder>),
+                    verify.run_if(resource_exists::<Playback>),
+                ),
+            );
+    }
+}
+
+/// Every fixed tick of one game, from the moment it leaves the menu, and enough to start it again
+/// the same way.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Replay {
+    /// Seed of the game's `GameRng`.
+    pub seed: u64,
+    pub players: usize,
+    /// The game is played back with the mode it was recorded with.
+    pub collision_mode: CollisionMode,
+    /// Hash of the `CollisionMatrix` it was recorded with, which it only plays back the same with.
+    pub collisions: u64,
+    pub ticks: Vec<Tick>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Tick {
+    pub input: PlayerInput,
+    /// `checksum` of the game state at the end of this tick, for every `CHECKSUM_INTERVAL`th tick.
+    pub checksum: Option<u64>,
+}
+
+impl Replay {
+    /// Encodes the replay as:
+    ///
+    /// - `SIRP`, then the format version as a little-endian `u16`
+    /// - the seed as a `u64`, the player count as a `u8`, the collision mode as a `u8` (0 for
+    ///   boxes, 1 for pixel-perfect), the collisions hash as a `u64` and the tick count as a `u32`
+    /// - for every tick, the movement scaled to an `i8`, a flags byte, and the checksum as a `u64`
+    ///   if the flags say there is one
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(28 + self.ticks.len() * 2);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(self.players as u8);
+        bytes.push(match self.collision_mode {
+            CollisionMode::Boxes => 0,
+            CollisionMode::PixelPerfect => 1,
+        });
+        bytes.extend_from_slice(&self.collisions.to_le_bytes());
+        bytes.extend_from_slice(&(self.ticks.len() as u32).to_le_bytes());
+
+        for tick in &self.ticks {
+            let mut flags = 0;
+            if tick.input.fire {
+                flags |= FIRE;
+            }
+            if tick.checksum.is_some() {
+                flags |= HAS_CHECKSUM;
+            }
+
+            bytes.push(encode_movement(tick.input.movement) as u8);
+            bytes.push(flags);
+            if let Some(checksum) = tick.checksum {
+                bytes.extend_from_slice(&checksum.to_le_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = Reader(bytes);
+
+        if reader.take::<4>()? != *MAGIC {
+            return Err(invalid("not a replay file"));
+        }
+        let version = u16::from_le_bytes(reader.take()?);
+        if version != VERSION {
+            return Err(invalid(format!("unsupported replay version {version}")));
+        }
+
+        let seed = u64::from_le_bytes(reader.take()?);
+        let [players] = reader.take()?;
+        if !(1..=2).contains(&players) {
+            return Err(invalid(format!("unsupported player count {players}")));
+        }
+        let collision_mode = match reader.take()? {
+            [0] => CollisionMode::Boxes,
+            [1] => CollisionMode::PixelPerfect,
+            [mode] => return Err(invalid(format!("unknown collision mode {mode}"))),
+        };
+        let collisions = u64::from_le_bytes(reader.take()?);
+        let count = u32::from_le_bytes(reader.take()?) as usize;
+        // every tick takes at least two bytes, so a count that can't fit is never allocated for
+        if count > reader.0.len() / 2 {
+            return Err(invalid("replay file is truncated"));
+        }
+
+        let mut ticks = Vec::with_capacity(count);
+        for _ in 0..count {
+            let [movement, flags] = reader.take()?;
+            let checksum = if flags & HAS_CHECKSUM != 0 {
+                Some(u64::from_le_bytes(reader.take()?))
+            } else {
+                None
+            };
+
+            ticks.push(Tick {
+                input: PlayerInput {
+                    movement: decode_movement(movement as i8),
+                    fire: flags & FIRE != 0,
+                },
+                checksum,
+            });
+        }
+
+        Ok(Self {
+            seed,
+            players: players as usize,
+            collision_mode,
+            collisions,
+            ticks,
+        })
+    }
+
+    /// Whether the replay was recorded with `matrix`, and so plays back the same with it.
+    pub fn recorded_with(&self, matrix: &CollisionMatrix) -> bool {
+        self.collisions == collisions_hash(matrix)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+}
+
+fn collisions_hash(matrix: &CollisionMatrix) -> u64 {
+    fnv1a_bytes(matrix.to_string().bytes())
+}
+
+fn encode_movement(movement: f32) -> i8 {
+    (movement.clamp(-1., 1.) * 127.).round() as i8
+}
+
+fn decode_movement(movement: i8) -> f32 {
+    movement as f32 / 127.
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let Some((bytes, rest)) = self.0.split_first_chunk::<N>() else {
+            return Err(invalid("replay file is truncated"));
+        };
+        self.0 = rest;
+        Ok(*bytes)
+    }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Records every game into `replay`, and saves it to `path` at game over if there is one.
+#[derive(Resource, Debug, Default)]
+pub struct Recorder {
+    pub replay: Replay,
+    pub path: Option<PathBuf>,
+    recording: bool,
+}
+
+impl Recorder {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self { path, ..default() }
+    }
+}
+
+/// Plays a replay back in place of the player's input, starting a game right away.
+#[derive(Resource, Debug)]
+pub struct Playback {
+    pub replay: Replay,
+    /// The first tick whose checksum didn't match the recording, if any.
+    pub desync: Option<usize>,
+    cursor: Option<usize>,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            desync: None,
+            cursor: None,
+        }
+    }
+
+    /// Whether every recorded tick has been played.
+    pub fn is_finished(&self) -> bool {
+        self.cursor
+            .is_some_and(|cursor| cursor >= self.replay.ticks.len())
+    }
+}
+
+/// Hash of the score, lives, and position of everything that can collide. Doesn't depend on the
+/// order entities are visited in.
+fn checksum(score: &Score, lives: &Lives, entities: &Query<&Transform, With<Hitbox>>) -> u64 {
+    let state = fnv1a(&[score.0 as u64, lives.0 as u64]);
+
+    entities.iter().fold(state, |sum, transform| {
+        let position = transform.translation;
+        sum.wrapping_add(fnv1a(&[
+            position.x.to_bits() as u64,
+            position.y.to_bits() as u64,
+        ]))
+    })
+}
+
+/// FNV-1a, which unlike the standard library's hasher is guaranteed to stay the same across
+/// builds, so replays can be shared.
+fn fnv1a(values: &[u64]) -> u64 {
+    fnv1a_bytes(values.iter().flat_map(|value| value.to_le_bytes()))
+}
+
+fn fnv1a_bytes(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn start_recording(
+    mut recorder: ResMut<Recorder>,
+    players: Res<Players>,
+    rng: Res<GameRng>,
+    collision_mode: Res<CollisionMode>,
+    collisions: Res<CollisionMatrix>,
+) {
+    recorder.replay = Replay {
+        seed: rng.seed(),
+        players: players.count,
+        collision_mode: *collision_mode,
+        collisions: collisions_hash(&collisions),
+        ticks: Vec::new(),
+    };
+    recorder.recording = true;
+}
+
+/// Rounds the movement to what the replay file can hold, so the recorded game plays exactly as
+/// it will be replayed.
+fn quantize_input(mut input: ResMut<PlayerInput>) {
+    input.movement = decode_movement(encode_movement(input.movement));
+}
+
+fn record(
+    mut recorder: ResMut<Recorder>,
+    input: Res<PlayerInput>,
+    score: Option<Res<Score>>,
+    lives: Option<Res<Lives>>,
+    entities: Query<&Transform, With<Hitbox>>,
+) {
+    if !recorder.recording {
+        return;
+    }
+
+    let (Some(score), Some(lives)) = (score, lives) else {
+        return;
+    };
+
+    let checksum = recorder
+        .replay
+        .ticks
+        .len()
+        .is_multiple_of(CHECKSUM_INTERVAL)
+        .then(|| checksum(&score, &lives, &entities));
+    recorder.replay.ticks.push(Tick {
+        input: *input,
+        checksum,
+    });
+}
+
+fn stop_recording(mut recorder: ResMut<Recorder>) {
+    recorder.recording = false;
+
+    let Some(path) = &recorder.path else {
+        return;
+    };
+
+    match recorder.replay.save(path) {
+        Ok(()) => info!("Saved replay to {}", path.display()),
+        Err(err) => warn!("Failed to save replay to {}: {err}", path.display()),
+    }
+}
+
+fn start_playback(
+    mut cmds: Commands,
+    playback: Res<Playback>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    cmds.insert_resource(Players::new(playback.replay.players));
+    cmds.insert_resource(GameRng::from_seed(playback.replay.seed));
+    cmds.insert_resource(playback.replay.collision_mode);
+    next_game_state.set(GameState::TurnChange);
+}
+
+fn rewind(mut playback: ResMut<Playback>) {
+    playback.cursor = Some(0);
+    playback.desync = None;
+}
+
+fn play_input(playback: Res<Playback>, mut input: ResMut<PlayerInput>) {
+    let Some(cursor) = playback.cursor else {
+        return;
+    };
+
+    if let Some(tick) = playback.replay.ticks.get(cursor) {
+        *input = tick.input;
+    }
+}
+
+fn verify(
+    mut playback: ResMut<Playback>,
+    score: Option<Res<Score>>,
+    lives: Option<Res<Lives>>,
+    entities: Query<&Transform, With<Hitbox>>,
+) {
+    let Some(cursor) = playback.cursor else {
+        return;
+    };
+
+    let Some(tick) = playback.replay.ticks.get(cursor).copied() else {
+        return;
+    };
+    playback.cursor = Some(cursor + 1);
+    if playback.is_finished() {
+        info!("Replay finished");
+    }
+
+    let (Some(expected), Some(score), Some(lives)) = (tick.checksum, score, lives) else {
+        return;
+    };
+
+    if playback.desync.is_none() && checksum(&score, &lives, &entities) != expected {
+        warn!("Replay desynced at tick {cursor}");
+        playback.desync = Some(cursor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shots::Layer;
+
+    fn replay() -> Replay {
+        Replay {
+            seed: 0xdead_beef_cafe,
+            players: 2,
+            collision_mode: CollisionMode::PixelPerfect,
+            collisions: collisions_hash(&CollisionMatrix::default()),
+            ticks: vec![
+                Tick {
+                    input: PlayerInput {
+                        movement: 1.,
+                        fire: true,
+                    },
+                    checksum: Some(u64::MAX),
+                },
+                Tick {
+                    input: PlayerInput {
+                        movement: -decode_movement(64),
+                        fire: false,
+                    },
+                    checksum: None,
+                },
+                Tick::default(),
+            ],
+        }
+    }
+
+    fn error(bytes: &[u8]) -> String {
+        Replay::from_bytes(bytes).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn round_trips() {
+        let replay = replay();
+        assert_eq!(Replay::from_bytes(&replay.to_bytes()).unwrap(), replay);
+        let empty = Replay {
+            players: 1,
+            ..default()
+        };
+        assert_eq!(Replay::from_bytes(&empty.to_bytes()).unwrap(), empty);
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let bytes = replay().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(Replay::from_bytes(&bytes[..len]).is_err(), "{len} bytes");
+        }
+    }
+
+    #[test]
+    fn rejects_tick_counts_longer_than_the_file() {
+        let empty = Replay {
+            players: 1,
+            ..default()
+        };
+        let mut bytes = empty.to_bytes();
+        bytes[24..28].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(error(&bytes), "replay file is truncated");
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut bytes = replay().to_bytes();
+        bytes[0] = b'X';
+        assert_eq!(error(&bytes), "not a replay file");
+    }
+
+    #[test]
+    fn rejects_unknown_collision_modes() {
+        let mut bytes = replay().to_bytes();
+        bytes[15] = 2;
+        assert_eq!(error(&bytes), "unknown collision mode 2");
+    }
+
+    #[test]
+    fn rejects_bad_player_counts() {
+        let mut bytes = replay().to_bytes();
+        for players in [0, 3, u8::MAX] {
+            bytes[14] = players;
+            assert_eq!(error(&bytes), format!("unsupported player count {players}"));
+        }
+    }
+
+    #[test]
+    fn knows_the_collisions_it_was_recorded_with() {
+        let replay = replay();
+        let mut matrix = CollisionMatrix::default();
+        assert!(replay.recorded_with(&matrix));
+
+        matrix.disable(Layer::ENEMY_SHOT, Layer::SHIELD);
+        assert!(!replay.recorded_with(&matrix));
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut bytes = replay().to_bytes();
+        bytes[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert_eq!(
+            error(&bytes),
+            format!("unsupported replay version {}", VERSION + 1)
+        );
+    }
+}
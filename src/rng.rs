@@ -0,0 +1,77 @@
+use crate::NewGame;
+use bevy::prelude::*;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<GameRng>() {
+            app.insert_resource(GameRng::default());
+        }
+
+        app.add_systems(NewGame, reseed);
+    }
+}
+
+/// The only source of randomness the rules may use, so that a seed and the player's input are
+/// enough to play a game again. Every game starts over from the seed; without a fixed one, each
+/// game gets a fresh seed.
+///
+/// Draws decide where the invaders' column tables start each wave, when the mystery ship comes
+/// and from which side, and whether a shot survives a clash in `shots::clash_shots`. Systems draw
+/// from it in tick phase order, so a seed gives the same draws on every run.
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    fixed: bool,
+    rng: StdRng,
+}
+
+impl GameRng {
+    /// Every game plays with this seed.
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            fixed: true,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Seed of the current game.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        let seed = rand::random();
+        Self {
+            seed,
+            fixed: false,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+}
+
+pub(crate) fn reseed(mut rng: ResMut<GameRng>) {
+    if !rng.fixed {
+        rng.seed = rand::random();
+    }
+    rng.rng = StdRng::seed_from_u64(rng.seed);
+}
@@ -1,27 +1,80 @@
+use crate::{storage, GameState, InGame, NewGame};
 use bevy::prelude::*;
 
 pub struct ScorePlugin;
 
+const HIGH_SCORE_KEY: &str = "high-score";
+
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(Score::default())
+            .insert_resource(HighScore::default())
+            .add_systems(NewGame, reset)
+            .add_systems(
+                Update,
+                update_high_score.run_if(resource_exists_and_changed::<Score>),
+            )
             .add_observer(on_points_removal);
     }
 }
 
+/// Loads the high score on startup and saves it at the end of every game, however it ends: game
+/// over, quit or restart.
+pub struct HighScoreStoragePlugin;
+
+impl Plugin for HighScoreStoragePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_high_score)
+            .add_systems(OnEnter(GameState::GameOver), save_high_score)
+            .add_systems(OnExit(InGame), save_high_score);
+    }
+}
+
 #[derive(Resource, Default)]
 pub struct Score(pub usize);
 
+/// Best score so far, persisted between sessions by `HighScoreStoragePlugin`.
+#[derive(Resource, Default)]
+pub struct HighScore(pub usize);
+
 #[derive(Component, Debug)]
 pub struct Points(pub usize);
 
+fn reset(mut score: ResMut<Score>) {
+    *score = Score::default();
+}
+
 fn on_points_removal(
     trigger: Trigger<OnRemove, Points>,
+    state: Res<State<GameState>>,
     points: Query<&Points>,
     mut score: ResMut<Score>,
 ) {
+    // invaders cleared away on the way back to the menu or into a new game weren't shot down
+    if matches!(state.get(), GameState::MainMenu | GameState::Restarting) {
+        return;
+    }
+
     let entity = trigger.entity();
     if let Ok(points) = points.get(entity) {
         score.0 += points.0;
     }
 }
+
+fn load_high_score(mut cmds: Commands) {
+    let high_score = storage::load(HIGH_SCORE_KEY)
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or_default();
+
+    cmds.insert_resource(HighScore(high_score));
+}
+
+fn update_high_score(score: Res<Score>, mut high_score: ResMut<HighScore>) {
+    if score.0 > high_score.0 {
+        high_score.0 = score.0;
+    }
+}
+
+fn save_high_score(high_score: Res<HighScore>) {
+    storage::save(HIGH_SCORE_KEY, &high_score.0.to_string());
+}
@@ -1,102 +1,251 @@
 use crate::{
     constants::*,
-    shots::{Collider, Hit},
-    GameState,
+    enemy::Enemy,
+    shots::{
+        Collider, CollisionMode, Hit, Hitbox, Layer, PixelMask, ENEMY_SHOT_EXPLOSION,
+        PLAYER_SHOT_EXPLOSION,
+    },
+    turns::{Players, Stash},
+    GameState, InGame, NewGame, PlayState, TickSet,
 };
 use bevy::prelude::*;
 
 pub struct ShieldPlugin;
 
-#[derive(Resource, Eq, PartialEq)]
-struct HasSpawnedSprites(bool);
+/// Height of the top row of shield blocks.
+const SHIELD_TOP: f32 = 77.0;
+
+/// Left edge of each of the four shields.
+const SHIELD_X: [f32; 4] = [33., 79., 124., 168.];
+
+/// Height of the line along the bottom of the arena.
+const GROUND_Y: f32 = 20.0;
+
+/// Shape of a shield, one pixel per `#`, taken from `sprites/shield.png`.
+const SHIELD_SHAPE: [&str; 16] = [
+    ".....#############....",
+    "...################...",
+    "..##################..",
+    ".####################.",
+    "######################",
+    "######################",
+    "######################",
+    "######################",
+    "######################",
+    "######################",
+    "######################",
+    "######################",
+    "#######.......########",
+    "######.........#######",
+    "#####...........######",
+    "#####...........######",
+];
 
 impl Plugin for ShieldPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), startup)
+        app.insert_resource(Stash::<StashedShields>::default())
+            .add_systems(NewGame, clear_stash)
+            .add_systems(OnEnter(GameState::Playing), startup)
+            .add_systems(OnEnter(GameState::LevelTransition), despawn_shields)
+            .add_systems(OnEnter(GameState::TurnChange), stash_shields)
             .add_systems(
                 FixedUpdate,
-                spawn_sprites.run_if(
-                    in_state(GameState::Playing)
-                        .and(is_sprite_loaded)
-                        .and(resource_exists_and_equals(HasSpawnedSprites(false))),
-                ),
+                erase_under_invaders
+                    .in_set(TickSet::Collide)
+                    .run_if(in_state(PlayState::Running)),
             );
     }
 }
 
-#[derive(Debug, Resource)]
-struct Asset(Handle<Image>);
+/// Draws each shield from its pixel mask, redrawing it whenever it is damaged.
+pub struct ShieldPresentationPlugin;
 
-fn startup(mut cmds: Commands, assets: Res<AssetServer>) {
-    cmds.insert_resource(Asset(assets.load("sprites/shield.png")));
-    cmds.insert_resource(HasSpawnedSprites(false));
+impl Plugin for ShieldPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(add_sprite)
+            .add_systems(Update, redraw_damaged);
+    }
 }
 
-fn is_sprite_loaded(sprite: Res<Asset>, asset_server: Res<AssetServer>) -> bool {
-    asset_server.load_state(&sprite.0).is_loaded()
-}
+/// The four shields and the ground line, one entity each, which shots and invaders wear away.
+#[derive(Component)]
+pub struct Shield;
 
-fn spawn_sprites(
+/// Position, hitbox and remaining pixels of each of a player's shields and their ground.
+type StashedShields = Vec<(Vec3, Hitbox, PixelMask)>;
+
+fn startup(
     mut cmds: Commands,
-    sprite: Res<Asset>,
-    images: Res<Assets<Image>>,
-    mut has_spawned_sprites: ResMut<HasSpawnedSprites>,
+    players: Res<Players>,
+    mut stashed: ResMut<Stash<StashedShields>>,
 ) {
-    has_spawned_sprites.0 = true;
-    let image = images.get(&sprite.0).unwrap();
-    let width = image.size().x as usize;
-    let height = image.size().y as usize;
-
-    let y_offset = 77.0;
-
-    // spawn each shield
-    for shield in [33., 79., 124., 168.].iter() {
-        for y in 0..height {
-            for x in 0..width {
-                let pixel_index = (y * width + x) * 4; // RGBA format
-                let alpha = image.data[pixel_index + 3]; // Check transparency
-
-                if alpha > 128 {
-                    // Only spawn blocks if pixel is visible
-                    let block_x = shield + x as f32;
-                    let block_y = y_offset - y as f32;
-
-                    cmds.spawn((
-                        ShieldBlock,
-                        Sprite {
-                            color: GREEN,
-                            custom_size: Some(Vec2::splat(1.)),
-                            ..default()
-                        },
-                        Transform::from_xyz(block_x, block_y, 0.0),
-                        Collider::shield_layer(),
-                    ))
-                    .observe(on_hit);
-                }
-            }
+    if let Some(shields) = stashed.resume(&players) {
+        for (position, hitbox, mask) in shields {
+            spawn_shield(&mut cmds, position, hitbox, mask);
         }
+        return;
     }
 
-    // spawn bottom line!()
-    for x in 0..(ARENA_SIZE.x as u32) {
-        cmds.spawn((
-            ShieldBlock,
-            Sprite {
-                color: GREEN,
-                custom_size: Some(Vec2::new(1., 0.5)),
-                ..default()
-            },
-            Transform::from_xyz(x as f32, 20., 0.0),
-            Collider::shield_layer(),
-        ))
-        .observe(on_hit);
+    let mask = PixelMask::from_rows(&SHIELD_SHAPE);
+    let half_size = mask.size().as_vec2() / 2.;
+    for x in SHIELD_X {
+        // pixel centres sit on whole units, starting from the top-left one at (x, SHIELD_TOP)
+        let center = Vec2::new(x - 0.5, SHIELD_TOP + 0.5) + Vec2::new(half_size.x, -half_size.y);
+        let hitbox = Hitbox(mask.size().as_vec2());
+        spawn_shield(&mut cmds, center.extend(0.), hitbox, mask.clone());
     }
+
+    let ground = PixelMask::filled(UVec2::new(ARENA_SIZE.x as u32, 1));
+    let center = Vec2::new(ground.size().x as f32 / 2. - 0.5, GROUND_Y);
+    // the ground is half as tall as the shields' pixels
+    let hitbox = Hitbox(ground.size().as_vec2() * Vec2::new(1., 0.5));
+    spawn_shield(&mut cmds, center.extend(0.), hitbox, ground);
 }
 
-#[derive(Component)]
-struct ShieldBlock;
+fn spawn_shield(cmds: &mut Commands, position: Vec3, hitbox: Hitbox, mask: PixelMask) {
+    cmds.spawn((
+        Shield,
+        StateScoped(InGame),
+        hitbox,
+        mask,
+        Transform::from_translation(position),
+        Collider::new(Layer::SHIELD),
+    ))
+    .observe(on_hit);
+}
 
-fn on_hit(trigger: Trigger<Hit>, mut cmds: Commands) {
+fn add_sprite(
+    trigger: Trigger<OnAdd, Shield>,
+    mut cmds: Commands,
+    mut images: ResMut<Assets<Image>>,
+    shields: Query<&PixelMask>,
+) {
     let entity = trigger.entity();
-    cmds.entity(entity).despawn();
+    if let Ok(mask) = shields.get(entity) {
+        cmds.entity(entity)
+            .insert(Sprite::from_image(images.add(mask.to_image(GREEN))));
+    }
+}
+
+/// Shields that lost pixels since they were last drawn.
+type Damaged = (With<Shield>, Changed<PixelMask>);
+
+/// Copies the damage done this frame to the shields' images, which uploads them again.
+fn redraw_damaged(
+    mut images: ResMut<Assets<Image>>,
+    shields: Query<(&PixelMask, &Sprite), Damaged>,
+) {
+    for (mask, sprite) in shields.iter() {
+        if let Some(image) = images.get_mut(&sprite.image) {
+            mask.draw(image, GREEN);
+        }
+    }
+}
+
+fn clear_stash(mut stashed: ResMut<Stash<StashedShields>>) {
+    *stashed = Stash::default();
+}
+
+fn stash_shields(
+    mut cmds: Commands,
+    players: Res<Players>,
+    mut stashed: ResMut<Stash<StashedShields>>,
+    shields: Query<(Entity, &Transform, &Hitbox, &PixelMask), With<Shield>>,
+) {
+    if !players.is_swapping() {
+        return;
+    }
+
+    let mut stash = Vec::new();
+    for (entity, transform, hitbox, mask) in shields.iter() {
+        stash.push((transform.translation, *hitbox, mask.clone()));
+        cmds.entity(entity).despawn();
+    }
+
+    stashed.put_aside(&players, stash);
+}
+
+/// Invaders marching through the shields wipe out every pixel they overlap.
+fn erase_under_invaders(
+    invaders: Query<(&Transform, &Hitbox), With<Enemy>>,
+    mut shields: Query<(&Transform, &Hitbox, &mut PixelMask), With<Shield>>,
+) {
+    let invaders: Vec<(Vec2, Vec2)> = invaders
+        .iter()
+        .map(|(transform, hitbox)| {
+            let half_size = hitbox.0 / 2.;
+            let center = transform.translation.truncate();
+            (center - half_size, center + half_size)
+        })
+        .filter(|(min, _)| min.y <= SHIELD_TOP)
+        .collect();
+
+    if invaders.is_empty() {
+        return;
+    }
+
+    for (transform, hitbox, mut mask) in shields.iter_mut() {
+        // only flag the shields that actually lost pixels as changed, so only they are redrawn
+        let cleared =
+            mask.bypass_change_detection()
+                .clear_where(hitbox.bounds(transform), |pixel| {
+                    invaders
+                        .iter()
+                        .any(|(min, max)| pixel.cmpge(*min).all() && pixel.cmple(*max).all())
+                });
+        if cleared {
+            mask.set_changed();
+        }
+    }
+}
+
+/// Shields are rebuilt from scratch at the start of every wave.
+fn despawn_shields(mut cmds: Commands, shields: Query<Entity, With<Shield>>) {
+    for entity in shields.iter() {
+        cmds.entity(entity).despawn();
+    }
+}
+
+/// Blows a hole the shape of the shot's explosion around where it hit, like the arcade.
+fn on_hit(
+    trigger: Trigger<Hit>,
+    mode: Res<CollisionMode>,
+    mut shields: Query<(&Transform, &Hitbox, &mut PixelMask)>,
+) {
+    let Ok((transform, hitbox, mut mask)) = shields.get_mut(trigger.entity()) else {
+        return;
+    };
+
+    let hit = trigger.event();
+    let explosion: &[&str] = if hit.other_layer.contains(Layer::PLAYER_SHOT) {
+        &PLAYER_SHOT_EXPLOSION
+    } else {
+        &ENEMY_SHOT_EXPLOSION
+    };
+
+    let bounds = hitbox.bounds(transform);
+    let solid_half_size = mask.solid_half_size(bounds, *mode);
+    // another shot may have blown it away earlier this tick
+    let Some(hit_pixel) = mask.solid_pixel_at(bounds, solid_half_size, hit.point) else {
+        return;
+    };
+
+    let explosion_center = hit.point.round().as_ivec2();
+    mask.clear_where(bounds, |pixel| {
+        pixel == hit_pixel || covers(explosion, pixel.round().as_ivec2() - explosion_center)
+    });
+}
+
+/// Whether an explosion mask centred on a pixel covers the pixel `offset` away from it.
+fn covers(mask: &[&str], offset: IVec2) -> bool {
+    let column = offset.x + mask[0].len() as i32 / 2;
+    // rows go from the top down
+    let row = mask.len() as i32 / 2 - 1 - offset.y;
+    if column < 0 || row < 0 {
+        return false;
+    }
+
+    mask.get(row as usize)
+        .and_then(|row| row.as_bytes().get(column as usize))
+        == Some(&b'#')
 }
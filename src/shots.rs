@@ -1,40 +1,105 @@
-use std::time::Duration;
+use std::{io, path::Path, time::Duration};
 
-use crate::{constants::*, GameState};
+use crate::{constants::*, enemy::Temporary, rng::GameRng, GameState, InGame, PlayState, TickSet};
 use bevy::{
-    math::bounding::{Aabb2d, IntersectsVolume as _},
+    ecs::{entity::EntityHashMap, system::SystemParam},
+    math::bounding::{Aabb2d, AabbCast2d, BoundingVolume as _, IntersectsVolume as _},
     prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
     time::common_conditions::on_timer,
+    utils::{HashMap, Parallel},
 };
-// use rand::seq::IndexedRandom as _;
+use rand::Rng as _;
 
 pub struct ShotPlugin;
 
+const PLAYER_SHOT_SIZE: Vec2 = Vec2::new(0.5, 4.);
+const ENEMY_SHOT_SIZE: Vec2 = Vec2::new(1., 4.);
+
+/// Shield pixels blown away around the point where a player shot hits, one per `#`. Also drawn
+/// where two shots run into each other.
+pub const PLAYER_SHOT_EXPLOSION: [&str; 8] = [
+    "#..#...#", "..#...#.", ".######.", "########", "########", ".######.", "..#..#..", "#...#..#",
+];
+
+/// Shield pixels blown away around the point where an invader shot hits, or the ground.
+pub const ENEMY_SHOT_EXPLOSION: [&str; 8] = [
+    "..#...", "#...#.", ".####.", "######", "#####.", ".####.", "#.#.#.", "..#..#",
+];
+
+/// Side of a `CollisionGrid` cell. A little larger than a shot, so one overlaps at most four cells.
+const GRID_CELL_SIZE: f32 = 8.;
+
 impl Plugin for ShotPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), startup)
+        app.insert_resource(CollisionGrid::default())
+            .init_resource::<CollisionMode>()
+            .init_resource::<ClashRules>()
+            .init_resource::<CollisionMatrix>()
+            .add_observer(remove_from_grid)
+            .add_systems(OnEnter(GameState::LevelTransition), despawn_projectiles)
+            .add_systems(OnEnter(GameState::TurnChange), despawn_projectiles)
+            .add_systems(OnEnter(PlayState::PlayerDying), despawn_enemy_shots)
             .add_systems(
                 FixedUpdate,
                 (
-                    move_player_shots,
-                    move_enemy_shots.run_if(on_timer(Duration::from_secs_f32(1. / 60.))),
-                    animate_enemy_shots.run_if(on_timer(Duration::from_secs_f32(2. / 60.))),
-                    check_collisions,
+                    (
+                        move_player_shots,
+                        move_enemy_shots.run_if(on_timer(Duration::from_secs_f32(1. / 60.))),
+                    )
+                        .in_set(TickSet::Move),
+                    (
+                        update_grid,
+                        clash_shots,
+                        check_collisions,
+                        remember_positions,
+                    )
+                        .chain()
+                        .in_set(TickSet::Collide),
                 )
-                    .run_if(in_state(GameState::Playing)),
+                    .run_if(in_state(PlayState::Running)),
+            );
+    }
+}
+
+/// Shot sprites, their animation and the player's firing sound, and the masks pixel-perfect
+/// collisions take from sprites.
+pub struct ShotPresentationPlugin;
+
+impl Plugin for ShotPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_assets)
+            .add_observer(add_player_shot_sprite)
+            .add_observer(add_enemy_shot_sprite)
+            .add_observer(explode_clash)
+            .add_systems(
+                FixedUpdate,
+                animate_enemy_shots
+                    .run_if(on_timer(Duration::from_secs_f32(2. / 60.)))
+                    .run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(
+                PostUpdate,
+                update_sprite_masks.run_if(resource_equals(CollisionMode::PixelPerfect)),
             );
     }
 }
 
 bitflags::bitflags! {
-    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
-    struct Layer: u32 {
-        const PLAYER_SHOT = 0b000001;
-        const ENEMY_SHOT  = 0b000010;
-        const WALL        = 0b000100;
-        const ENEMY       = 0b001000;
-        const PLAYER      = 0b010000;
-        const SHIELD      = 0b100000;
+    /// What kind of thing a `Collider` is. The `CollisionMatrix` says which kinds collide, and can
+    /// add kinds of its own on the bits left over.
+    #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+    pub struct Layer: u32 {
+        const PLAYER_SHOT = 0b0000001;
+        const ENEMY_SHOT  = 0b0000010;
+        const WALL        = 0b0000100;
+        const ENEMY       = 0b0001000;
+        const PLAYER      = 0b0010000;
+        const SHIELD      = 0b0100000;
+        const UFO         = 0b1000000;
     }
 }
 
@@ -42,60 +107,354 @@ bitflags::bitflags! {
 #[component(storage = "SparseSet")]
 pub struct PlayerShot;
 
-#[derive(Component, Default)]
+#[derive(Component)]
 #[component(storage = "SparseSet")]
-struct EnemyShot;
+pub struct EnemyShot(pub EnemyShotKind);
 
-#[derive(Component, Debug)]
+/// The three invader shots from the arcade. Each has a single shot object on the cabinet, so at
+/// most one of each kind is in flight at a time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EnemyShotKind {
+    /// Aimed at the column above the player.
+    #[default]
+    Rolling,
+    Plunger,
+    Squiggly,
+}
+
+impl EnemyShotKind {
+    /// The kind the invaders try to fire after this one.
+    pub fn next(self) -> Self {
+        match self {
+            EnemyShotKind::Rolling => EnemyShotKind::Plunger,
+            EnemyShotKind::Plunger => EnemyShotKind::Squiggly,
+            EnemyShotKind::Squiggly => EnemyShotKind::Rolling,
+        }
+    }
+}
+
+/// How an invader shot fares when the player's shot runs into it. The player's shot is always
+/// destroyed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClashRule {
+    /// Whether the shots collide at all, rather than pass through each other.
+    pub collides: bool,
+    /// Odds, from 0 to 1, that the invader shot carries on.
+    pub survival_chance: f64,
+}
+
+impl Default for ClashRule {
+    fn default() -> Self {
+        Self {
+            collides: true,
+            survival_chance: 0.,
+        }
+    }
+}
+
+/// How each kind of invader shot fares against the player's shot. Insert one before the app runs
+/// to change the rules.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct ClashRules {
+    pub rolling: ClashRule,
+    pub plunger: ClashRule,
+    pub squiggly: ClashRule,
+}
+
+impl Default for ClashRules {
+    fn default() -> Self {
+        Self {
+            // the shot aimed at the player is the hardest to shoot down
+            rolling: ClashRule {
+                collides: true,
+                survival_chance: 0.5,
+            },
+            plunger: ClashRule::default(),
+            squiggly: ClashRule::default(),
+        }
+    }
+}
+
+impl ClashRules {
+    pub fn rule(&self, kind: EnemyShotKind) -> ClashRule {
+        match kind {
+            EnemyShotKind::Rolling => self.rolling,
+            EnemyShotKind::Plunger => self.plunger,
+            EnemyShotKind::Squiggly => self.squiggly,
+        }
+    }
+}
+
+/// Something shots can hit, or a shot itself. What it collides with is up to the
+/// `CollisionMatrix`.
+#[derive(Component, Debug, Clone, Copy)]
 pub struct Collider {
-    layer: Layer,
-    mask: Layer,
+    pub layer: Layer,
 }
 
 #[derive(Component, Debug)]
 pub struct Projectile;
 
+/// Where a projectile was at the end of the last tick. Shots move further in a tick than shield
+/// blocks are tall, so collisions test everything they passed through since.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PreviousPosition(pub Vec2);
+
+/// Size of an entity for collisions. Walls go without one and use their scale instead.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Hitbox(pub Vec2);
+
+impl Hitbox {
+    /// The whole box, which a `PixelMask` is stretched over.
+    pub fn bounds(&self, transform: &Transform) -> Aabb2d {
+        Aabb2d::new(transform.translation.truncate(), size(self, transform) / 2.)
+    }
+}
+
+/// How closely shots have to come to what they hit. Set it before the app runs, e.g. with
+/// `--pixel-perfect`.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionMode {
+    /// Colliders are boxes two thirds the height of their `Hitbox`, and shields are solid on the
+    /// same share of each pixel.
+    #[default]
+    Boxes,
+    /// Colliders are their whole `Hitbox`, and only the solid pixels of a `PixelMask`. Invaders,
+    /// the cannon and the mystery ship take theirs from their sprites, so collisions depend on the
+    /// sprites being loaded and a headless replay of such a game won't play out the same.
+    PixelPerfect,
+}
+
+impl CollisionMode {
+    /// What a `Hitbox` is divided by to get the half size of its box.
+    fn divisor(self) -> Vec2 {
+        match self {
+            CollisionMode::Boxes => Vec2::new(2.0, 3.0),
+            CollisionMode::PixelPerfect => Vec2::splat(2.0),
+        }
+    }
+}
+
 impl Collider {
-    pub fn player_layer() -> Self {
-        Self {
-            layer: Layer::PLAYER,
-            mask: Layer::ENEMY_SHOT,
+    pub fn new(layer: Layer) -> Self {
+        Self { layer }
+    }
+}
+
+/// Which layers collide with which, always both ways, and the name of every layer. Starts out with
+/// the game's own layers; new kinds of collider get a layer of their own with `add_layer`, or from
+/// a file given with `--collisions`.
+///
+/// Only projectiles look for what they hit: an entity with `Projectile`, `PreviousPosition`,
+/// `Hitbox` and `Collider` hits the colliders it sweeps into, and other projectiles it clashes
+/// with. Two layers that are never on a projectile don't meet, whatever the matrix says, so a
+/// falling power-up that should hit the cannon has to be a `Projectile` itself.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct CollisionMatrix {
+    layers: Vec<LayerEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct LayerEntry {
+    name: String,
+    layer: Layer,
+    /// Every layer it collides with.
+    mask: Layer,
+}
+
+impl Default for CollisionMatrix {
+    fn default() -> Self {
+        let mut matrix = Self::without_collisions();
+        for (layer, others) in [
+            (
+                Layer::PLAYER_SHOT,
+                Layer::ENEMY | Layer::WALL | Layer::SHIELD | Layer::UFO | Layer::ENEMY_SHOT,
+            ),
+            (
+                Layer::ENEMY_SHOT,
+                Layer::PLAYER | Layer::WALL | Layer::SHIELD,
+            ),
+        ] {
+            matrix.enable(layer, others);
         }
+        matrix
     }
+}
 
-    pub fn enemy_layer() -> Self {
+impl CollisionMatrix {
+    /// The game's own layers, none of which collide.
+    fn without_collisions() -> Self {
         Self {
-            layer: Layer::ENEMY,
-            mask: Layer::PLAYER_SHOT,
+            layers: Layer::all()
+                .iter_names()
+                .map(|(name, layer)| LayerEntry {
+                    name: name.to_string(),
+                    layer,
+                    mask: Layer::empty(),
+                })
+                .collect(),
         }
     }
 
-    pub fn wall_layer() -> Self {
-        Self {
-            layer: Layer::WALL,
-            mask: Layer::PLAYER_SHOT | Layer::ENEMY_SHOT,
+    /// The layer going by `name`, if there is one.
+    pub fn layer(&self, name: &str) -> Option<Layer> {
+        self.layers
+            .iter()
+            .find(|entry| entry.name == name)
+            .map(|entry| entry.layer)
+    }
+
+    pub fn name(&self, layer: Layer) -> Option<&str> {
+        self.layers
+            .iter()
+            .find(|entry| entry.layer == layer)
+            .map(|entry| entry.name.as_str())
+    }
+
+    /// The layer going by `name`, added on the first free bit if there isn't one yet. `None` once
+    /// all 32 are taken. Things on it that move and hit by themselves need to be `Projectile`s.
+    pub fn add_layer(&mut self, name: &str) -> Option<Layer> {
+        if let Some(layer) = self.layer(name) {
+            return Some(layer);
         }
+
+        let taken = self
+            .layers
+            .iter()
+            .fold(Layer::empty(), |taken, entry| taken | entry.layer);
+        let free = (!taken.bits()).trailing_zeros();
+        let layer = Layer::from_bits_retain(1u32.checked_shl(free)?);
+        self.layers.push(LayerEntry {
+            name: name.to_string(),
+            layer,
+            mask: Layer::empty(),
+        });
+        Some(layer)
     }
 
-    pub fn shield_layer() -> Self {
-        Self {
-            layer: Layer::SHIELD,
-            mask: Layer::PLAYER_SHOT | Layer::ENEMY_SHOT,
+    /// Makes `layer` collide with every one of `others`, and them with it.
+    pub fn enable(&mut self, layer: Layer, others: Layer) {
+        self.set(layer, others, true);
+    }
+
+    /// Stops `layer` colliding with any of `others`, and them with it.
+    pub fn disable(&mut self, layer: Layer, others: Layer) {
+        self.set(layer, others, false);
+    }
+
+    fn set(&mut self, layer: Layer, others: Layer, collide: bool) {
+        for entry in &mut self.layers {
+            let with = if entry.layer.intersects(layer) {
+                others
+            } else if entry.layer.intersects(others) {
+                layer
+            } else {
+                continue;
+            };
+            entry.mask.set(with, collide);
+        }
+    }
+
+    /// Every layer that `layer` collides with.
+    pub fn mask(&self, layer: Layer) -> Layer {
+        self.layers
+            .iter()
+            .filter(|entry| entry.layer.intersects(layer))
+            .fold(Layer::empty(), |mask, entry| mask | entry.mask)
+    }
+
+    pub fn collides(&self, a: Layer, b: Layer) -> bool {
+        self.mask(a).intersects(b)
+    }
+
+    /// One line per layer, with the name of every layer it collides with after a colon, e.g.
+    /// `ENEMY_SHOT: PLAYER WALL SHIELD`. Collisions go both ways, so each needs listing only once.
+    /// Names that aren't one of the game's layers add a layer; lines starting with `#` are
+    /// comments. Only what the text lists collides.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut matrix = Self::without_collisions();
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, others) = line
+                .split_once(':')
+                .ok_or_else(|| invalid(format!("expected `LAYER: OTHER...`, got `{line}`")))?;
+            let mut layer = |name: &str| {
+                matrix
+                    .add_layer(name)
+                    .ok_or_else(|| invalid(format!("no room for layer {name}")))
+            };
+
+            let layer_of_line = layer(name.trim())?;
+            let others = others
+                .split_whitespace()
+                .map(&mut layer)
+                .collect::<io::Result<Vec<_>>>()?;
+            for other in others {
+                matrix.enable(layer_of_line, other);
+            }
         }
+
+        Ok(matrix)
     }
 
-    pub fn should_collide(a: &Collider, b: &Collider) -> bool {
-        a.mask.intersects(b.layer) && b.mask.intersects(a.layer)
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
     }
 }
 
+impl std::fmt::Display for CollisionMatrix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.layers {
+            write!(f, "{}:", entry.name)?;
+            for other in self
+                .layers
+                .iter()
+                .filter(|other| entry.mask.intersects(other.layer))
+            {
+                write!(f, " {}", other.name)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Squiggly, rolling and plunger shot sprites, in that order.
+#[derive(Resource)]
+struct EnemyShotSpritesWithAtlas([Sprite; 3]);
+
 #[derive(Resource)]
-pub struct EnemyShotSpritesWithAtlas([Sprite; 3]);
+struct PlayerShotAssets {
+    sprite: Handle<Image>,
+    sound: Handle<AudioSource>,
+    /// Drawn where it runs into an invader shot.
+    explosion: Handle<Image>,
+}
 
-fn startup(
+impl EnemyShotSpritesWithAtlas {
+    fn sprite(&self, kind: EnemyShotKind) -> Sprite {
+        let index = match kind {
+            EnemyShotKind::Squiggly => 0,
+            EnemyShotKind::Rolling => 1,
+            EnemyShotKind::Plunger => 2,
+        };
+        self.0[index].clone()
+    }
+}
+
+fn load_assets(
     mut cmds: Commands,
     assets: Res<AssetServer>,
     mut atlas: ResMut<Assets<TextureAtlasLayout>>,
+    mut images: ResMut<Assets<Image>>,
 ) {
     fn load_sprite(
         name: &str,
@@ -118,7 +477,7 @@ fn startup(
                 index: 0,
             },
         );
-        sprite.custom_size = Some(Vec2::new(1., 4.));
+        sprite.custom_size = Some(ENEMY_SHOT_SIZE);
         sprite
     }
 
@@ -143,50 +502,95 @@ fn startup(
         ),
     ]));
 
-    // load assets
-    let _ = assets.load::<Image>("sprites/shots/player.png");
-    let _ = assets.load::<AudioSource>("sounds/player-shot.ogg");
+    cmds.insert_resource(PlayerShotAssets {
+        sprite: assets.load("sprites/shots/player.png"),
+        sound: assets.load("sounds/player-shot.ogg"),
+        explosion: images.add(PixelMask::from_rows(&PLAYER_SHOT_EXPLOSION).to_image(Color::WHITE)),
+    });
+}
+
+fn add_player_shot_sprite(
+    trigger: Trigger<OnAdd, PlayerShot>,
+    mut cmds: Commands,
+    assets: Res<PlayerShotAssets>,
+) {
+    cmds.entity(trigger.entity()).insert(Sprite {
+        image: assets.sprite.clone(),
+        custom_size: Some(PLAYER_SHOT_SIZE),
+        ..default()
+    });
+    cmds.spawn(AudioPlayer::new(assets.sound.clone()));
 }
 
-pub fn spawn_player_shots(mut cmds: Commands, assets: Res<AssetServer>, position: Vec2) {
-    let projectile = assets.load("sprites/shots/player.png");
-    let mut sprite = Sprite::from_image(projectile);
-    sprite.custom_size = Some(Vec2::new(0.5, 4.));
-    cmds.spawn(AudioPlayer::new(assets.load("sounds/player-shot.ogg")));
+fn add_enemy_shot_sprite(
+    trigger: Trigger<OnAdd, EnemyShot>,
+    mut cmds: Commands,
+    sprites: Res<EnemyShotSpritesWithAtlas>,
+    shots: Query<&EnemyShot>,
+) {
+    let entity = trigger.entity();
+    if let Ok(shot) = shots.get(entity) {
+        cmds.entity(entity).insert(sprites.sprite(shot.0));
+    }
+}
+
+/// A small explosion where the player's shot ran into an invader shot.
+fn explode_clash(trigger: Trigger<Hit>, mut cmds: Commands, assets: Res<PlayerShotAssets>) {
+    let hit = trigger.event();
+    if !hit.layer.contains(Layer::PLAYER_SHOT) || !hit.other_layer.contains(Layer::ENEMY_SHOT) {
+        return;
+    }
+
+    cmds.spawn((
+        Sprite {
+            image: assets.explosion.clone(),
+            custom_size: Some(Vec2::splat(8.)),
+            ..default()
+        },
+        Transform::from_translation(hit.point.extend(0.)),
+        Temporary::from_seconds(0.25),
+        StateScoped(InGame),
+    ));
+}
+
+pub fn spawn_player_shots(mut cmds: Commands, position: Vec2) {
     cmds.spawn((
-        sprite,
         Transform::from_xyz(position.x, position.y, 0.0),
+        Hitbox(PLAYER_SHOT_SIZE),
         PlayerShot,
-        Collider {
-            layer: Layer::PLAYER_SHOT,
-            mask: Layer::ENEMY | Layer::WALL | Layer::SHIELD,
-        },
+        Collider::new(Layer::PLAYER_SHOT),
         Projectile,
+        PreviousPosition(position),
+        StateScoped(InGame),
     ))
     .observe(on_hit_destroy);
 }
 
+fn despawn_projectiles(mut cmds: Commands, projectiles: Query<Entity, With<Projectile>>) {
+    for entity in projectiles.iter() {
+        cmds.entity(entity).despawn();
+    }
+}
+
+fn despawn_enemy_shots(mut cmds: Commands, shots: Query<Entity, With<EnemyShot>>) {
+    for entity in shots.iter() {
+        cmds.entity(entity).despawn();
+    }
+}
+
 pub fn on_hit_destroy(trigger: Trigger<Hit>, mut cmds: Commands) {
     cmds.entity(trigger.entity()).despawn();
 }
 
-pub fn spawn_enemy_shots(
-    mut cmds: Commands,
-    sprite: Res<EnemyShotSpritesWithAtlas>,
-    position: Vec2,
-) {
-    // let mut rng = rand::rng();
-    let sprite = sprite.0.clone();
+pub fn spawn_enemy_shots(mut cmds: Commands, position: Vec2, kind: EnemyShotKind) {
     cmds.spawn((
-        // sprite.choose(&mut rng).unwrap().clone(),
-        sprite[0].clone(),
         Transform::from_xyz(position.x, position.y, 0.0),
-        EnemyShot,
-        Collider {
-            layer: Layer::ENEMY_SHOT,
-            mask: Layer::PLAYER | Layer::WALL | Layer::SHIELD,
-        },
+        Hitbox(ENEMY_SHOT_SIZE),
+        EnemyShot(kind),
+        Collider::new(Layer::ENEMY_SHOT),
         Projectile,
+        PreviousPosition(position),
+        StateScoped(InGame),
     ))
     .observe(on_hit_destroy);
 }
@@ -211,68 +615,947 @@ fn animate_enemy_shots(mut shots: Query<&mut Sprite, With<EnemyShot>>) {
     }
 }
 
-#[derive(Event)]
-pub struct Hit;
+/// Sent to both entities of a collision.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Hit {
+    /// The entity it collided with.
+    pub other: Entity,
+    /// Where they touched.
+    pub point: Vec2,
+    /// Layer of the entity being hit.
+    pub layer: Layer,
+    /// Layer of the entity it collided with.
+    pub other_layer: Layer,
+}
 
-fn check_collisions(
-    par_cmds: ParallelCommands,
-    projectiles: Query<(Entity, &Transform, &Sprite, &Collider), With<Projectile>>,
-    colliders: Query<(Entity, &Transform, Option<&Sprite>, &Collider)>,
-    images: Res<Assets<Image>>,
-) {
-    projectiles.par_iter().for_each(
-        |(projectile_entity, projectile_transform, proj_sprite, proj_collider)| {
-            for (coll_entity, coll_transform, coll_sprite, coll_collider) in colliders.iter() {
-                if projectile_entity == coll_entity {
-                    // it's the same entity
-                    continue;
+/// Where a collider ran into something.
+#[derive(Debug, Clone, Copy)]
+pub struct Contact {
+    pub entity: Entity,
+    /// How far the collider had moved when they touched.
+    pub distance: f32,
+    pub point: Vec2,
+    pub layer: Layer,
+    /// Column and row of the pixel it ran into, for colliders with a `PixelMask`.
+    pub pixel: Option<UVec2>,
+}
+
+/// A collider's box moving in a straight line over a tick.
+#[derive(Debug, Clone, Copy)]
+pub struct Sweep {
+    pub from: Vec2,
+    pub to: Vec2,
+    pub half_size: Vec2,
+}
+
+impl Sweep {
+    pub fn length(&self) -> f32 {
+        self.from.distance(self.to)
+    }
+
+    /// The box once it has moved `distance` along the way.
+    pub fn at(&self, distance: f32) -> Aabb2d {
+        let center = self.from + (self.to - self.from).normalize_or_zero() * distance;
+        Aabb2d::new(center, self.half_size)
+    }
+
+    /// Everything the box covers on its way.
+    pub fn bounds(&self) -> Aabb2d {
+        self.at(0.).merge(&self.at(self.length()))
+    }
+
+    /// How far along the box first touches `target`, if it does.
+    pub fn entry(&self, target: Aabb2d) -> Option<f32> {
+        match Dir2::new(self.to - self.from) {
+            Ok(direction) => AabbCast2d::new(
+                Aabb2d::new(Vec2::ZERO, self.half_size),
+                self.from,
+                direction,
+                self.length(),
+            )
+            .aabb_collision_at(target),
+            // it didn't move
+            Err(_) => self.at(0.).intersects(&target).then_some(0.),
+        }
+    }
+}
+
+/// Which pixels of a collider are solid, for colliders with holes in them like the shields or
+/// irregular sprites. The mask is stretched over the collider's `Hitbox`, the top row first.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct PixelMask {
+    size: UVec2,
+    pixels: Vec<bool>,
+}
+
+impl PixelMask {
+    /// One row per string, with a `#` for every solid pixel.
+    pub fn from_rows(rows: &[&str]) -> Self {
+        let size = UVec2::new(rows[0].len() as u32, rows.len() as u32);
+        let pixels = rows
+            .iter()
+            .flat_map(|row| row.chars().map(|pixel| pixel == '#'))
+            .collect();
+        Self { size, pixels }
+    }
+
+    pub fn filled(size: UVec2) -> Self {
+        Self {
+            size,
+            pixels: vec![true; (size.x * size.y) as usize],
+        }
+    }
+
+    /// The pixels of `rect` in an image that are at least half opaque.
+    pub fn from_image(image: &Image, rect: URect) -> Self {
+        let size = rect.size();
+        let pixels = (rect.min.y..rect.max.y)
+            .flat_map(|y| (rect.min.x..rect.max.x).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                image
+                    .get_color_at(x, y)
+                    .is_ok_and(|color| color.alpha() >= 0.5)
+            })
+            .collect();
+        Self { size, pixels }
+    }
+
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// An image the size of the mask, `color` where it's solid and transparent elsewhere.
+    pub fn to_image(&self, color: Color) -> Image {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: self.size.x,
+                height: self.size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0; 4],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        self.draw(&mut image, color);
+        image
+    }
+
+    /// Draws the mask over an image from `to_image`, e.g. once pixels were cleared.
+    pub fn draw(&self, image: &mut Image, color: Color) {
+        let color = color.to_srgba().to_u8_array();
+        for row in 0..self.size.y {
+            for column in 0..self.size.x {
+                let index = self.index(column, row) * 4;
+                let pixel = if self.get(column, row) { color } else { [0; 4] };
+                image.data[index..index + 4].copy_from_slice(&pixel);
+            }
+        }
+    }
+
+    pub fn get(&self, column: u32, row: u32) -> bool {
+        column < self.size.x && row < self.size.y && self.pixels[self.index(column, row)]
+    }
+
+    fn index(&self, column: u32, row: u32) -> usize {
+        (row * self.size.x + column) as usize
+    }
+
+    /// Size of a pixel in the world, for a mask stretched over `bounds`.
+    fn pixel_size(&self, bounds: Aabb2d) -> Vec2 {
+        bounds.half_size() * 2. / self.size.as_vec2()
+    }
+
+    /// Box of a pixel in the world, for a mask stretched over `bounds`.
+    pub fn pixel_aabb(&self, bounds: Aabb2d, column: u32, row: u32) -> Aabb2d {
+        let pixel = self.pixel_size(bounds);
+        let min = bounds.min + pixel * Vec2::new(column as f32, (self.size.y - 1 - row) as f32);
+        Aabb2d {
+            min,
+            max: min + pixel,
+        }
+    }
+
+    /// Clears every solid pixel whose centre `erase` picks, returning whether any was.
+    pub fn clear_where(&mut self, bounds: Aabb2d, mut erase: impl FnMut(Vec2) -> bool) -> bool {
+        let mut cleared = false;
+        for row in 0..self.size.y {
+            for column in 0..self.size.x {
+                let index = self.index(column, row);
+                if self.pixels[index] && erase(self.pixel_aabb(bounds, column, row).center()) {
+                    self.pixels[index] = false;
+                    cleared = true;
                 }
+            }
+        }
+        cleared
+    }
+
+    /// Centre of the first solid pixel whose solid part holds `point`, for a mask stretched over
+    /// `bounds`.
+    pub fn solid_pixel_at(
+        &self,
+        bounds: Aabb2d,
+        solid_half_size: Vec2,
+        point: Vec2,
+    ) -> Option<Vec2> {
+        (0..self.size.y)
+            .flat_map(|row| (0..self.size.x).map(move |column| (column, row)))
+            .filter(|(column, row)| self.get(*column, *row))
+            .map(|(column, row)| self.pixel_aabb(bounds, column, row).center())
+            .find(|pixel| Aabb2d::new(*pixel, solid_half_size).closest_point(point) == point)
+    }
+
+    /// How far each pixel is solid around its centre, the same share of it that a `Hitbox` the
+    /// size of a pixel would be.
+    pub fn solid_half_size(&self, bounds: Aabb2d, mode: CollisionMode) -> Vec2 {
+        self.pixel_size(bounds) / mode.divisor()
+    }
+
+    /// The first solid pixel a sweep runs into, for a mask stretched over `bounds` whose pixels
+    /// are `solid_half_size` around their centres. Ties go to the pixel nearest the top left.
+    pub fn contact(
+        &self,
+        sweep: &Sweep,
+        bounds: Aabb2d,
+        solid_half_size: Vec2,
+        contact: Contact,
+    ) -> Option<Contact> {
+        let swept = sweep.bounds();
+        let pixel = self.pixel_size(bounds);
+        // boxes that only touch still collide
+        let min = ((swept.min - bounds.min) / pixel).ceil() - 1.;
+        let max = ((swept.max - bounds.min) / pixel).floor() + 1.;
+        let min = min.max(Vec2::ZERO).as_uvec2();
+        let max = max.max(Vec2::ZERO).as_uvec2().min(self.size);
 
-                if !Collider::should_collide(proj_collider, coll_collider) {
-                    // these two colliders don't collide (e.g. enemy with walls)
+        let mut first: Option<Contact> = None;
+        // rows count from the top
+        for y in (min.y..max.y).rev() {
+            let row = self.size.y - 1 - y;
+            for column in min.x..max.x {
+                if !self.get(column, row) {
                     continue;
                 }
 
-                if (projectile_transform.translation.y - coll_transform.translation.y).abs() > 10. {
-                    // all colliders move vertically, so we can rule out collisions if they're too
-                    // far away on the Y axis
+                let solid = Aabb2d::new(
+                    self.pixel_aabb(bounds, column, row).center(),
+                    solid_half_size,
+                );
+                let Some(distance) = sweep.entry(solid) else {
                     continue;
+                };
+                if first.is_none_or(|first| distance < first.distance) {
+                    first = Some(Contact {
+                        distance,
+                        point: solid.closest_point(sweep.at(distance).center()),
+                        pixel: Some(UVec2::new(column, row)),
+                        ..contact
+                    });
                 }
+            }
+        }
+        first
+    }
+}
 
-                let box_a = Aabb2d::new(
-                    projectile_transform.translation.truncate(),
-                    size(proj_sprite, projectile_transform, &images) / Vec2::new(2.0, 3.0),
-                );
+/// Takes the collider's `PixelMask` from the opaque pixels of its sprite's current frame, in
+/// `CollisionMode::PixelPerfect`.
+#[derive(Component, Debug, Default)]
+pub struct MaskFromSprite {
+    /// Image and atlas frame the mask was last taken from.
+    frame: Option<(AssetId<Image>, Option<usize>)>,
+}
 
-                let box_b = Aabb2d::new(
-                    coll_transform.translation.truncate(),
-                    coll_sprite
-                        .map(|s| size(s, coll_transform, &images) / Vec2::new(2.0, 3.0))
-                        .unwrap_or_else(|| coll_transform.scale.truncate()),
-                );
+/// Uniform grid of everything that shots can hit, per `Collider` layer, so a shot is only tested
+/// against what is in the cells it overlaps. Entities are moved in it when their `Transform`,
+/// `Hitbox` or `Collider` changes; shots themselves aren't in it.
+#[derive(Resource, Default)]
+pub struct CollisionGrid {
+    layers: HashMap<Layer, HashMap<IVec2, Vec<Entity>>>,
+    entries: EntityHashMap<GridEntry>,
+}
 
-                if !box_a.intersects(&box_b) {
-                    // they don't intersect
-                    continue;
-                }
+#[derive(Clone, Copy)]
+struct GridEntry {
+    aabb: Aabb2d,
+    layer: Layer,
+}
+
+impl CollisionGrid {
+    /// Number of entities in the grid.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
 
-                par_cmds.command_scope(|mut cmds| {
-                    cmds.trigger_targets(Hit, projectile_entity);
-                    cmds.trigger_targets(Hit, coll_entity);
+    fn insert(&mut self, entity: Entity, aabb: Aabb2d, layer: Layer) {
+        self.remove(entity);
+
+        let cells = self.layers.entry(layer).or_default();
+        for cell in cells_of(aabb) {
+            cells.entry(cell).or_default().push(entity);
+        }
+
+        self.entries.insert(entity, GridEntry { aabb, layer });
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        let Some(entry) = self.entries.remove(&entity) else {
+            return;
+        };
+
+        let Some(cells) = self.layers.get_mut(&entry.layer) else {
+            return;
+        };
+        for cell in cells_of(entry.aabb) {
+            if let Some(entities) = cells.get_mut(&cell) {
+                entities.retain(|other| *other != entity);
+            }
+        }
+    }
+
+    /// Where a sweeping collider touches the box of everything in the grid on one of the `mask`
+    /// layers, in entity order.
+    pub fn contacts(&self, sweep: &Sweep, mask: Layer) -> Vec<Contact> {
+        self.hits(sweep.bounds(), mask)
+            .into_iter()
+            .filter_map(|entity| {
+                let entry = &self.entries[&entity];
+                let distance = sweep.entry(entry.aabb)?;
+                Some(Contact {
+                    entity,
+                    distance,
+                    point: entry.aabb.closest_point(sweep.at(distance).center()),
+                    layer: entry.layer,
+                    pixel: None,
                 })
+            })
+            .collect()
+    }
+
+    /// Everything in the grid on one of the `mask` layers that overlaps `aabb`, in entity order.
+    pub fn hits(&self, aabb: Aabb2d, mask: Layer) -> Vec<Entity> {
+        let mut hits: Vec<Entity> = self
+            .layers
+            .iter()
+            .filter(|(layer, _)| mask.intersects(**layer))
+            .flat_map(|(_, cells)| cells_of(aabb).filter_map(|cell| cells.get(&cell)))
+            .flatten()
+            .copied()
+            .filter(|entity| self.entries[entity].aabb.intersects(&aabb))
+            .collect();
+
+        // big colliders span several cells
+        hits.sort_unstable();
+        hits.dedup();
+        hits
+    }
+}
+
+fn cells_of(aabb: Aabb2d) -> impl Iterator<Item = IVec2> {
+    let min = (aabb.min / GRID_CELL_SIZE).floor().as_ivec2();
+    let max = (aabb.max / GRID_CELL_SIZE).floor().as_ivec2();
+    (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+}
+
+/// Box used for collisions. Walls go without a `Hitbox` and use their scale instead, and colliders
+/// with a `PixelMask` cover all of it, as the mask says which parts are solid.
+fn aabb(
+    transform: &Transform,
+    hitbox: Option<&Hitbox>,
+    mask: Option<&PixelMask>,
+    mode: CollisionMode,
+) -> Aabb2d {
+    match (hitbox, mask) {
+        (Some(hitbox), Some(_)) => hitbox.bounds(transform),
+        (Some(hitbox), None) => Aabb2d::new(
+            transform.translation.truncate(),
+            size(hitbox, transform) / mode.divisor(),
+        ),
+        (None, _) => Aabb2d::new(transform.translation.truncate(), transform.scale.truncate()),
+    }
+}
+
+/// Colliders other than shots that moved, changed size or layer, or just appeared.
+type Moved = (
+    Without<Projectile>,
+    Or<(
+        Changed<Transform>,
+        Changed<Hitbox>,
+        Changed<Collider>,
+        Added<PixelMask>,
+    )>,
+);
+
+/// What decides how much room a collider takes up besides its scale.
+type Shape = (Option<&'static Hitbox>, Option<&'static PixelMask>);
+
+/// Puts colliders that moved since the last tick back in the grid where they now are.
+pub fn update_grid(
+    mut grid: ResMut<CollisionGrid>,
+    mode: Res<CollisionMode>,
+    colliders: Query<(Entity, &Transform, Shape, &Collider), Moved>,
+) {
+    for (entity, transform, (hitbox, mask), collider) in colliders.iter() {
+        grid.insert(entity, aabb(transform, hitbox, mask, *mode), collider.layer);
+    }
+}
+
+fn remove_from_grid(trigger: Trigger<OnRemove, Collider>, mut grid: ResMut<CollisionGrid>) {
+    grid.remove(trigger.entity());
+}
+
+/// Where a projectile's box went since the last tick.
+fn sweep(
+    transform: &Transform,
+    hitbox: &Hitbox,
+    previous: &PreviousPosition,
+    mode: CollisionMode,
+) -> Sweep {
+    Sweep {
+        from: previous.0,
+        to: transform.translation.truncate(),
+        half_size: aabb(transform, Some(hitbox), None, mode).half_size(),
+    }
+}
+
+/// What shots can run into, and the rules for running into it.
+#[derive(SystemParam)]
+struct Colliders<'w, 's> {
+    grid: Res<'w, CollisionGrid>,
+    matrix: Res<'w, CollisionMatrix>,
+    mode: Res<'w, CollisionMode>,
+    masks: Query<'w, 's, (&'static PixelMask, &'static Hitbox, &'static Transform)>,
+}
+
+impl Colliders<'_, '_> {
+    /// The first thing in the grid that a sweeping collider on `layer` runs into; through a
+    /// `PixelMask`, that's the first solid pixel. Ties go to the lowest entity.
+    fn first_contact(&self, sweep: &Sweep, layer: Layer) -> Option<Contact> {
+        self.grid
+            .contacts(sweep, self.matrix.mask(layer))
+            .into_iter()
+            .filter_map(|contact| match self.masks.get(contact.entity) {
+                Ok((mask, hitbox, transform)) => {
+                    let bounds = hitbox.bounds(transform);
+                    let solid_half_size = mask.solid_half_size(bounds, *self.mode);
+                    mask.contact(sweep, bounds, solid_half_size, contact)
+                }
+                Err(_) => Some(contact),
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+}
+
+/// How far through the tick a collider that moved `length` had gone `distance` of the way.
+fn fraction(distance: f32, length: f32) -> f32 {
+    if length > 0. {
+        distance / length
+    } else {
+        0.
+    }
+}
+
+/// Everything about a projectile that decides where it goes and what it hits.
+type ProjectileData = (
+    Entity,
+    &'static Transform,
+    &'static Hitbox,
+    &'static Collider,
+    &'static PreviousPosition,
+);
+
+/// Shots that run into each other this tick, before either reaches anything else, are destroyed
+/// as the `ClashRules` say. A shot that survives isn't sent a `Hit`, and still hits whatever it
+/// reaches afterwards in `check_collisions`. Clashes are settled in the order they happen, ties in
+/// entity order, so the `GameRng` is drawn from the same way on every run.
+fn clash_shots(
+    mut cmds: Commands,
+    mut rng: ResMut<GameRng>,
+    rules: Res<ClashRules>,
+    colliders: Colliders,
+    projectiles: Query<(ProjectileData, Option<&EnemyShot>)>,
+) {
+    let mut shots: Vec<_> = projectiles
+        .iter()
+        .map(
+            |((entity, transform, hitbox, collider, previous), enemy_shot)| {
+                let sweep = sweep(transform, hitbox, previous, *colliders.mode);
+                // how far through the tick it reaches something else
+                let reach = colliders
+                    .first_contact(&sweep, collider.layer)
+                    .map_or(f32::INFINITY, |contact| {
+                        fraction(contact.distance, sweep.length())
+                    });
+                (entity, sweep, collider, enemy_shot, reach)
+            },
+        )
+        .collect();
+    shots.sort_unstable_by_key(|(entity, ..)| *entity);
+
+    let mut clashes = Vec::new();
+    for (index, a) in shots.iter().enumerate() {
+        for b in &shots[index + 1..] {
+            if !colliders.matrix.collides(a.2.layer, b.2.layer) {
+                continue;
+            }
+
+            // as seen from `b`, only `a` moves
+            let relative = Sweep {
+                from: a.1.from - b.1.from,
+                to: a.1.to - b.1.to,
+                half_size: a.1.half_size,
+            };
+            let Some(distance) = relative.entry(Aabb2d::new(Vec2::ZERO, b.1.half_size)) else {
+                continue;
+            };
+
+            let time = fraction(distance, relative.length());
+            if time <= a.4 && time <= b.4 {
+                clashes.push((time, a, b));
+            }
+        }
+    }
+    clashes.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut clashed = Vec::new();
+    for (time, a, b) in clashes {
+        if clashed.contains(&a.0) || clashed.contains(&b.0) {
+            continue;
+        }
+
+        let (invader_shot, other) = if a.3.is_some() { (a, b) } else { (b, a) };
+        let rule = invader_shot
+            .3
+            .map_or(ClashRule::default(), |shot| rules.rule(shot.0));
+        if !rule.collides {
+            continue;
+        }
+        clashed.extend([a.0, b.0]);
+
+        let survives = rule.survival_chance > 0. && rng.random_bool(rule.survival_chance);
+        let position = |shot: &(_, Sweep, _, _, _)| shot.1.from + (shot.1.to - shot.1.from) * time;
+        let point = Aabb2d::new(position(b), b.1.half_size).closest_point(position(a));
+
+        for (shot, against) in [(invader_shot, other), (other, invader_shot)] {
+            if survives && shot.0 == invader_shot.0 {
+                continue;
             }
+            cmds.trigger_targets(
+                Hit {
+                    other: against.0,
+                    point,
+                    layer: shot.2.layer,
+                    other_layer: against.2.layer,
+                },
+                shot.0,
+            );
+        }
+    }
+}
+
+/// Each projectile hits the first thing along the way it travelled this tick, and nothing else.
+/// Hits are sent in entity order, and what two projectiles hit at once (the same pixel, for a
+/// mask) is only hit once, so every run plays out the same way.
+fn check_collisions(
+    mut cmds: Commands,
+    mut found: Local<Parallel<Vec<(Entity, Layer, Contact)>>>,
+    colliders: Colliders,
+    projectiles: Query<ProjectileData>,
+) {
+    projectiles.par_iter().for_each(
+        |(projectile_entity, projectile_transform, proj_hitbox, proj_collider, previous)| {
+            let sweep = sweep(projectile_transform, proj_hitbox, previous, *colliders.mode);
+            let Some(contact) = colliders.first_contact(&sweep, proj_collider.layer) else {
+                return;
+            };
+
+            found.scope(|hits| hits.push((projectile_entity, proj_collider.layer, contact)));
         },
     );
+
+    let mut hits: Vec<_> = found.drain().collect();
+    hits.sort_unstable_by_key(|(projectile, _, _)| *projectile);
+
+    let mut already_hit = Vec::with_capacity(hits.len());
+    for (projectile, layer, contact) in hits {
+        cmds.trigger_targets(
+            Hit {
+                other: contact.entity,
+                point: contact.point,
+                layer,
+                other_layer: contact.layer,
+            },
+            projectile,
+        );
+
+        let target = (contact.entity, contact.pixel);
+        if already_hit.contains(&target) {
+            continue;
+        }
+        already_hit.push(target);
+        cmds.trigger_targets(
+            Hit {
+                other: projectile,
+                point: contact.point,
+                layer: contact.layer,
+                other_layer: layer,
+            },
+            contact.entity,
+        );
+    }
 }
 
-fn size(sprite: &Sprite, transform: &Transform, images: &Res<Assets<Image>>) -> Vec2 {
-    let size = if let Some(custom_size) = sprite.custom_size {
-        custom_size
-    } else if let Some(image) = images.get(&sprite.image) {
-        image.size().as_vec2()
-    } else {
-        Vec2::new(1.0, 1.0)
-    };
+/// Keeps the masks taken from sprites in step with the frame they show. Frames change in `Update`,
+/// so the mask is up to date for the next fixed tick.
+fn update_sprite_masks(
+    mut cmds: Commands,
+    mut cache: Local<HashMap<(AssetId<Image>, URect), PixelMask>>,
+    images: Res<Assets<Image>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    mut sprites: Query<(Entity, &Sprite, &mut MaskFromSprite)>,
+) {
+    for (entity, sprite, mut source) in sprites.iter_mut() {
+        let frame = (
+            sprite.image.id(),
+            sprite.texture_atlas.as_ref().map(|atlas| atlas.index),
+        );
+        if source.frame == Some(frame) {
+            continue;
+        }
+
+        // try again once the image and its layout have loaded
+        let Some(image) = images.get(&sprite.image) else {
+            continue;
+        };
+        let rect = match &sprite.texture_atlas {
+            Some(atlas) => match layouts
+                .get(&atlas.layout)
+                .and_then(|layout| layout.textures.get(atlas.index))
+            {
+                Some(rect) => *rect,
+                None => continue,
+            },
+            None => URect::from_corners(UVec2::ZERO, image.size()),
+        };
+
+        let mask = cache
+            .entry((frame.0, rect))
+            .or_insert_with(|| PixelMask::from_image(image, rect));
+        cmds.entity(entity).insert(mask.clone());
+        source.frame = Some(frame);
+    }
+}
+
+fn remember_positions(mut projectiles: Query<(&mut PreviousPosition, &Transform)>) {
+    for (mut previous, transform) in projectiles.iter_mut() {
+        previous.0 = transform.translation.truncate();
+    }
+}
+
+fn size(hitbox: &Hitbox, transform: &Transform) -> Vec2 {
+    hitbox.0 * transform.scale.truncate()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What `PixelMask::contact` fills in, for a collider that isn't in the world.
+    fn contact_with_mask() -> Contact {
+        Contact {
+            entity: Entity::PLACEHOLDER,
+            distance: 0.,
+            point: Vec2::ZERO,
+            layer: Layer::SHIELD,
+            pixel: None,
+        }
+    }
+
+    #[test]
+    fn fast_shots_hit_a_shield_pixel_they_jump_over() {
+        let mask = PixelMask::from_rows(&["#"]);
+        let bounds = Aabb2d::new(Vec2::new(10., 50.), Vec2::splat(0.5));
+        let solid_half_size = mask.solid_half_size(bounds, CollisionMode::PixelPerfect);
+        // from well below the pixel to well above it in one step
+        let sweep = Sweep {
+            from: Vec2::new(10., 40.),
+            to: Vec2::new(10., 60.),
+            half_size: Vec2::new(0.5, 1.5),
+        };
+        let pixel = Aabb2d::new(bounds.center(), solid_half_size);
+        assert!(!sweep.at(0.).intersects(&pixel));
+        assert!(!sweep.at(sweep.length()).intersects(&pixel));
+
+        assert_eq!(sweep.entry(pixel), Some(8.));
+        let contact = mask
+            .contact(&sweep, bounds, solid_half_size, contact_with_mask())
+            .unwrap();
+        assert_eq!(contact.distance, 8.);
+        assert_eq!(contact.pixel, Some(UVec2::new(0, 0)));
+        assert_eq!(contact.point, Vec2::new(10., 49.5));
+    }
+
+    #[test]
+    fn fast_shots_hit_the_ground_they_jump_over() {
+        let ground = PixelMask::filled(UVec2::new(224, 1));
+        // half a unit tall, like the shields' pixels are wide
+        let bounds = Aabb2d::new(Vec2::new(111.5, 20.), Vec2::new(112., 0.25));
+        let solid_half_size = ground.solid_half_size(bounds, CollisionMode::PixelPerfect);
+        let sweep = Sweep {
+            from: Vec2::new(50., 30.),
+            to: Vec2::new(50., 10.),
+            half_size: Vec2::new(0.25, 1.5),
+        };
+        assert!(!sweep.at(0.).intersects(&bounds));
+        assert!(!sweep.at(sweep.length()).intersects(&bounds));
+
+        assert_eq!(sweep.entry(bounds), Some(8.25));
+        let contact = ground
+            .contact(&sweep, bounds, solid_half_size, contact_with_mask())
+            .unwrap();
+        assert_eq!(contact.distance, 8.25);
+        assert_eq!(contact.pixel, Some(UVec2::new(50, 0)));
+        assert_eq!(contact.point, Vec2::new(50., 20.25));
+    }
+
+    #[test]
+    fn sweeps_miss_what_they_pass_beside() {
+        let sweep = Sweep {
+            from: Vec2::new(10., 40.),
+            to: Vec2::new(10., 60.),
+            half_size: Vec2::new(0.5, 1.5),
+        };
+        assert_eq!(
+            sweep.entry(Aabb2d::new(Vec2::new(12., 50.), Vec2::splat(0.5))),
+            None
+        );
+
+        // a shot that stood still only hits what it is in
+        let still = Sweep {
+            to: sweep.from,
+            ..sweep
+        };
+        assert_eq!(
+            still.entry(Aabb2d::new(Vec2::new(10., 50.), Vec2::splat(0.5))),
+            None
+        );
+        assert_eq!(
+            still.entry(Aabb2d::new(Vec2::new(10., 41.), Vec2::splat(0.5))),
+            Some(0.)
+        );
+    }
 
-    size * transform.scale.truncate()
+    /// Every cell of the grid that holds `entity`.
+    fn cells_holding(grid: &CollisionGrid, entity: Entity) -> Vec<IVec2> {
+        let mut cells: Vec<IVec2> = grid
+            .layers
+            .values()
+            .flat_map(|cells| cells.iter())
+            .filter(|(_, entities)| entities.contains(&entity))
+            .map(|(cell, _)| *cell)
+            .collect();
+        cells.sort_unstable_by_key(|cell| (cell.y, cell.x));
+        cells
+    }
+
+    #[test]
+    fn collision_grid_follows_colliders_as_they_move_and_go() {
+        let mut grid = CollisionGrid::default();
+        let invader = Entity::from_raw(1);
+
+        grid.insert(
+            invader,
+            Aabb2d::new(Vec2::new(4., 4.), Vec2::splat(2.)),
+            Layer::ENEMY,
+        );
+        assert_eq!(grid.len(), 1);
+        assert_eq!(cells_holding(&grid, invader), [IVec2::new(0, 0)]);
+
+        // straddling four cells
+        grid.insert(
+            invader,
+            Aabb2d::new(Vec2::new(16., 8.), Vec2::splat(2.)),
+            Layer::ENEMY,
+        );
+        assert_eq!(grid.len(), 1);
+        assert_eq!(
+            cells_holding(&grid, invader),
+            [
+                IVec2::new(1, 0),
+                IVec2::new(2, 0),
+                IVec2::new(1, 1),
+                IVec2::new(2, 1)
+            ]
+        );
+        let where_it_was = Aabb2d::new(Vec2::new(4., 4.), Vec2::splat(2.));
+        assert!(grid.hits(where_it_was, Layer::ENEMY).is_empty());
+
+        grid.remove(invader);
+        assert!(grid.is_empty());
+        assert!(cells_holding(&grid, invader).is_empty());
+        let where_it_is = Aabb2d::new(Vec2::new(16., 8.), Vec2::splat(2.));
+        assert!(grid.hits(where_it_is, Layer::ENEMY).is_empty());
+
+        // removing twice is harmless
+        grid.remove(invader);
+        assert!(grid.is_empty());
+    }
+
+    #[test]
+    fn collision_grid_hits_only_overlapping_colliders_on_the_mask() {
+        let mut grid = CollisionGrid::default();
+        let [invader, neighbour, shield, far_away, near_miss] =
+            [1, 2, 3, 4, 5].map(Entity::from_raw);
+        let half_size = Vec2::splat(1.);
+        grid.insert(
+            invader,
+            Aabb2d::new(Vec2::new(7., 4.), half_size),
+            Layer::ENEMY,
+        );
+        // in the next cell over, and the next one up
+        grid.insert(
+            neighbour,
+            Aabb2d::new(Vec2::new(9., 9.), half_size),
+            Layer::ENEMY,
+        );
+        grid.insert(
+            shield,
+            Aabb2d::new(Vec2::new(7., 4.), half_size),
+            Layer::SHIELD,
+        );
+        grid.insert(
+            far_away,
+            Aabb2d::new(Vec2::new(60., 4.), half_size),
+            Layer::ENEMY,
+        );
+        // in a cell the shot overlaps, but not under it
+        grid.insert(
+            near_miss,
+            Aabb2d::new(Vec2::new(14., 2.), half_size),
+            Layer::ENEMY,
+        );
+
+        let shot = Aabb2d::new(Vec2::new(8., 6.), Vec2::new(1., 3.));
+        assert_eq!(grid.hits(shot, Layer::ENEMY), [invader, neighbour]);
+        assert_eq!(grid.hits(shot, Layer::SHIELD), [shield]);
+        assert_eq!(
+            grid.hits(shot, Layer::ENEMY | Layer::SHIELD),
+            [invader, neighbour, shield]
+        );
+        assert!(grid.hits(shot, Layer::PLAYER).is_empty());
+    }
+
+    #[test]
+    fn collision_matrix_round_trips_through_text() {
+        let matrix = CollisionMatrix::default();
+        assert_eq!(CollisionMatrix::parse(&matrix.to_string()).unwrap(), matrix);
+
+        let mut custom = CollisionMatrix::parse("POWER_UP: PLAYER\n").unwrap();
+        custom.enable(Layer::PLAYER_SHOT, Layer::UFO);
+        assert_eq!(CollisionMatrix::parse(&custom.to_string()).unwrap(), custom);
+    }
+
+    #[test]
+    fn collision_matrix_parses_both_ways() {
+        let matrix = CollisionMatrix::parse(
+            "# shots only hit the ground\n\nPLAYER_SHOT: WALL\n  ENEMY_SHOT :WALL  SHIELD\n",
+        )
+        .unwrap();
+
+        assert!(matrix.collides(Layer::WALL, Layer::PLAYER_SHOT));
+        assert!(matrix.collides(Layer::SHIELD, Layer::ENEMY_SHOT));
+        assert!(!matrix.collides(Layer::PLAYER_SHOT, Layer::ENEMY));
+        assert_eq!(
+            matrix.mask(Layer::WALL),
+            Layer::PLAYER_SHOT | Layer::ENEMY_SHOT
+        );
+    }
+
+    #[test]
+    fn collision_matrix_adds_unknown_layers() {
+        let matrix = CollisionMatrix::parse("POWER_UP: PLAYER BUNKER\n").unwrap();
+        let power_up = matrix.layer("POWER_UP").unwrap();
+        let bunker = matrix.layer("BUNKER").unwrap();
+
+        assert_eq!(power_up, Layer::from_bits_retain(Layer::UFO.bits() << 1));
+        assert_eq!(bunker, Layer::from_bits_retain(Layer::UFO.bits() << 2));
+        assert_eq!(matrix.name(bunker), Some("BUNKER"));
+        assert!(matrix.collides(Layer::PLAYER, power_up));
+        assert!(!matrix.collides(Layer::PLAYER, bunker));
+    }
+
+    #[test]
+    fn collision_matrix_rejects_lines_without_a_colon() {
+        let error = CollisionMatrix::parse("PLAYER_SHOT: ENEMY\nPLAYER WALL\n").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            "expected `LAYER: OTHER...`, got `PLAYER WALL`"
+        );
+    }
+
+    #[test]
+    fn collision_matrix_has_room_for_32_layers() {
+        let mut matrix = CollisionMatrix::default();
+        let free = 32 - Layer::all().iter().count();
+        for index in 0..free {
+            assert!(matrix.add_layer(&format!("EXTRA_{index}")).is_some());
+        }
+
+        assert_eq!(matrix.add_layer("ONE_TOO_MANY"), None);
+        // layers that exist are still found
+        assert_eq!(matrix.add_layer("EXTRA_0"), matrix.layer("EXTRA_0"));
+
+        let too_many: String = (0..=free)
+            .map(|index| format!("EXTRA_{index}:\n"))
+            .collect();
+        let error = CollisionMatrix::parse(&too_many).unwrap_err();
+        assert_eq!(error.to_string(), format!("no room for layer EXTRA_{free}"));
+    }
+
+    #[derive(Resource, Default)]
+    struct Hits(Vec<Hit>);
+
+    #[test]
+    fn added_layers_hit_from_a_projectile() {
+        let mut matrix = CollisionMatrix::default();
+        let power_up = matrix.add_layer("POWER_UP").unwrap();
+        matrix.enable(power_up, Layer::PLAYER);
+
+        let mut app = App::new();
+        app.insert_resource(matrix)
+            .init_resource::<CollisionGrid>()
+            .init_resource::<CollisionMode>()
+            .init_resource::<Hits>()
+            .add_systems(Update, (update_grid, check_collisions).chain());
+
+        app.world_mut().spawn((
+            Transform::from_xyz(50., 16., 0.),
+            Hitbox(Vec2::new(13., 8.)),
+            Collider::new(Layer::PLAYER),
+        ));
+        // falling onto the cannon from above, through it within the tick
+        app.world_mut()
+            .spawn((
+                Projectile,
+                PreviousPosition(Vec2::new(50., 40.)),
+                Transform::from_xyz(50., 10., 0.),
+                Hitbox(Vec2::new(3., 3.)),
+                Collider::new(power_up),
+            ))
+            .observe(|trigger: Trigger<Hit>, mut hits: ResMut<Hits>| {
+                hits.0.push(*trigger.event());
+            });
+        app.update();
+
+        let hits = &app.world().resource::<Hits>().0;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].layer, power_up);
+        assert_eq!(hits[0].other_layer, Layer::PLAYER);
+    }
 }
@@ -0,0 +1,61 @@
+//! Tiny key/value persistence: one file per key under the XDG data directory on native, and
+//! `localStorage` on the web.
+
+use bevy::prelude::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+const APP_DIR: &str = "bevy-space-invaders";
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(key: &str) -> Option<String> {
+    std::fs::read_to_string(path(key)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(key: &str, value: &str) {
+    let Some(path) = path(key) else {
+        warn!("no data directory to save {key} to");
+        return;
+    };
+
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, value));
+
+    if let Err(err) = result {
+        warn!("failed to save {key} to {}: {err}", path.display());
+    }
+}
+
+/// `$XDG_DATA_HOME/bevy-space-invaders/<key>`, falling back to `~/.local/share` as the spec says.
+#[cfg(not(target_arch = "wasm32"))]
+fn path(key: &str) -> Option<std::path::PathBuf> {
+    use std::{env, path::PathBuf};
+
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))?;
+
+    Some(data_home.join(APP_DIR).join(key))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load(key: &str) -> Option<String> {
+    local_storage()?.get_item(key).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(key: &str, value: &str) {
+    let result = local_storage().map(|storage| storage.set_item(key, value));
+
+    if !matches!(result, Some(Ok(()))) {
+        warn!("failed to save {key} to localStorage");
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
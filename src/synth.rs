@@ -0,0 +1,115 @@
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::{
+    audio::{AddAudioSource, Source},
+    prelude::*,
+};
+
+pub struct SynthPlugin;
+
+impl Plugin for SynthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<Tone>();
+    }
+}
+
+const SAMPLE_RATE: u32 = 44_100;
+
+/// A procedurally generated square-wave tone, for the sounds the cabinet made with discrete
+/// circuitry rather than samples.
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct Tone {
+    pub frequency: f32,
+    /// Frequency modulation, as `(rate, depth)` in Hz.
+    pub vibrato: Option<(f32, f32)>,
+    /// How long the tone lasts, fading out linearly. `None` plays until the audio entity is
+    /// despawned.
+    pub duration: Option<Duration>,
+    pub volume: f32,
+}
+
+impl Tone {
+    pub fn new(frequency: f32) -> Self {
+        Self {
+            frequency,
+            vibrato: None,
+            duration: None,
+            volume: 0.2,
+        }
+    }
+
+    pub fn with_vibrato(mut self, rate: f32, depth: f32) -> Self {
+        self.vibrato = Some((rate, depth));
+        self
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+}
+
+pub struct ToneDecoder {
+    tone: Tone,
+    phase: f32,
+    sample: u32,
+    total_samples: Option<u32>,
+}
+
+impl Iterator for ToneDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let envelope = match self.total_samples {
+            Some(total) if self.sample >= total => return None,
+            Some(total) => 1. - self.sample as f32 / total as f32,
+            None => 1.,
+        };
+
+        let t = self.sample as f32 / SAMPLE_RATE as f32;
+        let frequency = match self.tone.vibrato {
+            Some((rate, depth)) => self.tone.frequency + depth * (TAU * rate * t).sin(),
+            None => self.tone.frequency,
+        };
+
+        self.phase = (self.phase + frequency / SAMPLE_RATE as f32).fract();
+        self.sample += 1;
+
+        let square = if self.phase < 0.5 { 1. } else { -1. };
+        Some(square * self.tone.volume * envelope)
+    }
+}
+
+impl Source for ToneDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.tone.duration
+    }
+}
+
+impl Decodable for Tone {
+    type DecoderItem = <ToneDecoder as Iterator>::Item;
+    type Decoder = ToneDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        ToneDecoder {
+            tone: self.clone(),
+            phase: 0.,
+            sample: 0,
+            total_samples: self
+                .duration
+                .map(|d| (d.as_secs_f32() * SAMPLE_RATE as f32) as u32),
+        }
+    }
+}
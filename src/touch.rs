@@ -0,0 +1,148 @@
+use crate::player::ReadInput;
+use bevy::{
+    input::{touch::Touches, InputSystem},
+    prelude::*,
+    text::FontSmoothing,
+    window::PrimaryWindow,
+};
+
+/// On-screen controls for phones and tablets, shown as soon as the screen is touched.
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            (
+                activate.run_if(not(resource_exists::<ButtonInput<TouchButton>>)),
+                (spawn_controls, press_buttons)
+                    .chain()
+                    .run_if(resource_exists::<ButtonInput<TouchButton>>),
+            )
+                .chain()
+                .after(InputSystem)
+                .before(ReadInput),
+        )
+        .add_systems(
+            Update,
+            highlight.run_if(resource_exists_and_changed::<ButtonInput<TouchButton>>),
+        );
+    }
+}
+
+/// The on-screen buttons. Their state is in a `ButtonInput<TouchButton>` resource, which only
+/// exists once the screen has been touched.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TouchButton {
+    Left,
+    Right,
+    Fire,
+    Pause,
+}
+
+const IDLE_COLOR: Color = Color::srgba(1., 1., 1., 0.08);
+const PRESSED_COLOR: Color = Color::srgba(1., 1., 1., 0.25);
+
+#[derive(Component)]
+struct TouchControls;
+
+fn activate(mut cmds: Commands, touches: Res<Touches>) {
+    if touches.any_just_pressed() {
+        cmds.insert_resource(ButtonInput::<TouchButton>::default());
+    }
+}
+
+fn spawn_controls(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    controls: Query<(), With<TouchControls>>,
+) {
+    use Val::*;
+
+    if !controls.is_empty() {
+        return;
+    }
+
+    let font = TextFont {
+        font: asset_server.load("font.ttf"),
+        font_size: 10.0,
+        font_smoothing: FontSmoothing::None,
+    };
+    let button = |width| {
+        (
+            Node {
+                width: Percent(width),
+                height: Percent(100.),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(IDLE_COLOR),
+            BorderRadius::all(Px(6.)),
+        )
+    };
+
+    // a strip along the bottom of the screen, below the cannon
+    cmds.spawn((
+        TouchControls,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Px(0.),
+            width: Percent(100.),
+            height: Percent(30.),
+            padding: UiRect::all(Px(4.)),
+            column_gap: Px(4.),
+            ..default()
+        },
+        GlobalZIndex(i32::MAX),
+    ))
+    .with_children(|parent| {
+        for (kind, width, label) in [
+            (TouchButton::Left, 28., "<"),
+            (TouchButton::Right, 28., ">"),
+            (TouchButton::Pause, 14., "II"),
+            (TouchButton::Fire, 30., "FIRE"),
+        ] {
+            parent
+                .spawn((kind, button(width)))
+                .with_child((Text::new(label), font.clone()));
+        }
+    });
+}
+
+/// A button is held while any finger is on it, so the cannon can move and fire at once.
+fn press_buttons(
+    touches: Res<Touches>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    buttons: Query<(&TouchButton, &ComputedNode, &GlobalTransform)>,
+    mut input: ResMut<ButtonInput<TouchButton>>,
+) {
+    input.clear();
+
+    for (button, node, transform) in &buttons {
+        // nodes are laid out in physical pixels, touches are in logical ones
+        let rect = Rect::from_center_size(transform.translation().truncate(), node.size());
+        let touched = touches
+            .iter()
+            .any(|touch| rect.contains(touch.position() * window.scale_factor()));
+
+        if touched {
+            input.press(*button);
+        } else {
+            input.release(*button);
+        }
+    }
+}
+
+fn highlight(
+    input: Res<ButtonInput<TouchButton>>,
+    mut buttons: Query<(&TouchButton, &mut BackgroundColor)>,
+) {
+    for (button, mut color) in &mut buttons {
+        color.0 = if input.pressed(*button) {
+            PRESSED_COLOR
+        } else {
+            IDLE_COLOR
+        };
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{
+    constants::*,
+    player::{Lives, ShotsFired},
+    score::Score,
+    wave::Wave,
+    GameState,
+};
+use bevy::{prelude::*, text::FontSmoothing};
+
+pub struct TurnsPlugin;
+
+/// How long the "PLAY PLAYER<n>" screen is shown before a turn starts.
+const INTERSTITIAL_SECONDS: f32 = 2.0;
+
+impl Plugin for TurnsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Players::new(1))
+            .add_systems(OnEnter(GameState::Restarting), restart)
+            .add_systems(OnEnter(GameState::TurnChange), setup)
+            .add_systems(FixedUpdate, update.run_if(in_state(GameState::TurnChange)))
+            .add_systems(OnExit(GameState::TurnChange), (swap, cleanup));
+    }
+}
+
+/// The "PLAY PLAYER<n>" screen shown between turns.
+pub struct TurnsPresentationPlugin;
+
+impl Plugin for TurnsPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::TurnChange), spawn_interstitial);
+    }
+}
+
+/// Who is playing. In two-player games the players alternate every time the active one loses a
+/// life, and everything that belongs to the waiting player is put aside in `stashed` until their
+/// next turn.
+#[derive(Resource, Debug)]
+pub struct Players {
+    pub count: usize,
+    /// Index of the player whose turn it is.
+    pub current: usize,
+    /// Index of the player who plays once the current turn change is over.
+    pub next: usize,
+    stashed: [PlayerState; 2],
+}
+
+/// The per-player resources that are swapped in and out between turns. Shields and formations are
+/// stashed by their own plugins, in a `Stash`.
+#[derive(Debug, Clone, Copy)]
+struct PlayerState {
+    score: usize,
+    lives: usize,
+    wave: usize,
+    shots_fired: usize,
+}
+
+impl Default for PlayerState {
+    fn default() -> Self {
+        Self {
+            score: 0,
+            lives: PLAYER_LIVES,
+            wave: Wave::default().0,
+            shots_fired: 0,
+        }
+    }
+}
+
+impl Players {
+    pub fn new(count: usize) -> Self {
+        Self {
+            count,
+            current: 0,
+            next: 0,
+            stashed: default(),
+        }
+    }
+
+    /// Whether the current turn change hands over to the other player.
+    pub fn is_swapping(&self) -> bool {
+        self.current != self.next
+    }
+
+    /// Score of the given player, or `None` if they are not in this game. Only meaningful for the
+    /// waiting player, as the current player's score lives in `Score`.
+    pub fn stashed_score(&self, player: usize) -> Option<usize> {
+        (player < self.count).then(|| self.stashed[player].score)
+    }
+
+    fn other(&self) -> usize {
+        (self.current + 1) % self.count
+    }
+
+    /// What happens after the current player lost a life, given the lives they have left.
+    pub fn after_death(&mut self, lives: usize) -> Option<GameState> {
+        let other = self.other();
+        if other != self.current && self.stashed[other].lives > 0 {
+            self.next = other;
+            Some(GameState::TurnChange)
+        } else if lives == 0 {
+            Some(GameState::GameOver)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whatever each player left behind of one part of the game, e.g. their shields, kept while the
+/// other player has their turn. Every game starts with it empty.
+#[derive(Resource)]
+pub struct Stash<T>([Option<T>; 2]);
+
+impl<T> Default for Stash<T> {
+    fn default() -> Self {
+        Self([None, None])
+    }
+}
+
+impl<T> Stash<T> {
+    /// Keeps what the current player leaves behind until their next turn.
+    pub fn put_aside(&mut self, players: &Players, value: T) {
+        self.0[players.current] = Some(value);
+    }
+
+    /// What the current player left behind at the end of their last turn, if they had one.
+    pub fn resume(&mut self, players: &Players) -> Option<T> {
+        self.0[players.current].take()
+    }
+}
+
+#[derive(Resource)]
+struct InterstitialTimer(Timer);
+
+/// Starts over with as many players as the game that was left.
+fn restart(
+    mut cmds: Commands,
+    players: Res<Players>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    cmds.insert_resource(Players::new(players.count));
+    next_game_state.set(GameState::TurnChange);
+}
+
+fn setup(mut cmds: Commands) {
+    cmds.insert_resource(InterstitialTimer(Timer::from_seconds(
+        INTERSTITIAL_SECONDS,
+        TimerMode::Once,
+    )));
+}
+
+fn spawn_interstitial(mut cmds: Commands, players: Res<Players>, asset_server: Res<AssetServer>) {
+    let font = TextFont {
+        font: asset_server.load("font.ttf"),
+        font_size: 14.0,
+        font_smoothing: FontSmoothing::None,
+    };
+
+    cmds.spawn((
+        StateScoped(GameState::TurnChange),
+        Node {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            Text::new(format!("PLAY PLAYER<{}>", players.next + 1)),
+            font,
+        ));
+    });
+}
+
+fn update(
+    time: Res<Time>,
+    mut timer: ResMut<InterstitialTimer>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    if timer.0.tick(time.delta()).finished() {
+        next_game_state.set(GameState::Playing);
+    }
+}
+
+/// Puts the outgoing player's resources aside and brings back the incoming player's.
+fn swap(
+    mut players: ResMut<Players>,
+    mut score: ResMut<Score>,
+    mut lives: ResMut<Lives>,
+    mut wave: ResMut<Wave>,
+    mut shots_fired: ResMut<ShotsFired>,
+) {
+    if !players.is_swapping() {
+        return;
+    }
+
+    let current = players.current;
+    players.stashed[current] = PlayerState {
+        score: score.0,
+        lives: lives.0,
+        wave: wave.0,
+        shots_fired: shots_fired.0,
+    };
+
+    let next = players.next;
+    let incoming = players.stashed[next];
+    score.0 = incoming.score;
+    lives.0 = incoming.lives;
+    wave.0 = incoming.wave;
+    shots_fired.0 = incoming.shots_fired;
+
+    players.current = next;
+}
+
+fn cleanup(mut cmds: Commands) {
+    cmds.remove_resource::<InterstitialTimer>();
+}
@@ -0,0 +1,198 @@
+use crate::{
+    constants::*,
+    enemy::{Enemy, Temporary},
+    player::ShotsFired,
+    rng::GameRng,
+    score::Points,
+    shots::{Collider, Hit, Hitbox, Layer, MaskFromSprite},
+    synth::Tone,
+    GameState, InGame, PlayState, TickSet,
+};
+use bevy::{prelude::*, text::FontSmoothing};
+use rand::Rng as _;
+
+pub struct UfoPlugin;
+
+/// The arcade sends a mystery ship across roughly every 25 seconds. The first one of a wave waits
+/// exactly that long, and each later one a time drawn from `SPAWN_SECONDS_RANGE`.
+const SPAWN_SECONDS: f32 = 25.6;
+const SPAWN_SECONDS_RANGE: std::ops::RangeInclusive<f32> = 20.0..=30.0;
+/// No mystery ship appears once fewer invaders than this remain.
+const MIN_INVADERS: usize = 8;
+const UFO_SIZE: Vec2 = Vec2::new(16., 7.);
+const UFO_SPEED: f32 = 0.5;
+const UFO_Y: f32 = ARENA_SIZE.y - 40.;
+
+/// Mystery points, indexed by how many shots the player has fired when the ship is hit.
+const MYSTERY_POINTS: [usize; 15] = [
+    100, 50, 50, 100, 150, 100, 100, 50, 300, 100, 100, 100, 50, 150, 100,
+];
+
+impl Plugin for UfoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), startup)
+            .add_systems(OnEnter(GameState::LevelTransition), despawn_ufo)
+            .add_systems(OnEnter(GameState::TurnChange), despawn_ufo)
+            .add_systems(OnEnter(GameState::GameOver), despawn_ufo)
+            .add_systems(
+                FixedUpdate,
+                (
+                    spawn_ufo.in_set(TickSet::Fire),
+                    move_ufo.in_set(TickSet::Move),
+                )
+                    .run_if(in_state(PlayState::Running)),
+            );
+    }
+}
+
+/// The mystery ship's sprite, its looping siren and the points shown where it was hit.
+pub struct UfoPresentationPlugin;
+
+impl Plugin for UfoPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_assets)
+            .add_observer(add_sprite)
+            .add_observer(show_points);
+    }
+}
+
+#[derive(Component)]
+struct Ufo {
+    direction: f32,
+}
+
+#[derive(Resource)]
+struct SpawnTimer(Timer);
+
+#[derive(Resource)]
+struct UfoAssets {
+    sprite: Handle<Image>,
+    sound: Handle<Tone>,
+    font: Handle<Font>,
+}
+
+fn startup(mut cmds: Commands) {
+    cmds.insert_resource(SpawnTimer(Timer::from_seconds(
+        SPAWN_SECONDS,
+        TimerMode::Once,
+    )));
+}
+
+fn load_assets(mut cmds: Commands, assets: Res<AssetServer>, mut tones: ResMut<Assets<Tone>>) {
+    cmds.insert_resource(UfoAssets {
+        sprite: assets.load("sprites/ufo.png"),
+        sound: tones.add(Tone::new(520.).with_vibrato(6., 180.)),
+        font: assets.load("font.ttf"),
+    });
+}
+
+fn spawn_ufo(
+    mut cmds: Commands,
+    time: Res<Time>,
+    mut timer: ResMut<SpawnTimer>,
+    mut rng: ResMut<GameRng>,
+    ufos: Query<(), With<Ufo>>,
+    enemies: Query<(), With<Enemy>>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    timer.0 = Timer::from_seconds(rng.random_range(SPAWN_SECONDS_RANGE), TimerMode::Once);
+
+    if !ufos.is_empty() || enemies.iter().count() < MIN_INVADERS {
+        return;
+    }
+
+    let (x, direction) = if rng.random_bool(0.5) {
+        (-UFO_SIZE.x / 2., 1.)
+    } else {
+        (ARENA_SIZE.x + UFO_SIZE.x / 2., -1.)
+    };
+
+    cmds.spawn((
+        Ufo { direction },
+        StateScoped(InGame),
+        Transform::from_xyz(x, UFO_Y, 0.0),
+        Hitbox(UFO_SIZE),
+        Collider::new(Layer::UFO),
+        // the actual value depends on the shot count at the time of the hit
+        Points(0),
+    ))
+    .observe(on_hit);
+}
+
+fn add_sprite(trigger: Trigger<OnAdd, Ufo>, mut cmds: Commands, assets: Res<UfoAssets>) {
+    cmds.entity(trigger.entity()).insert((
+        Sprite {
+            image: assets.sprite.clone(),
+            custom_size: Some(UFO_SIZE),
+            color: Color::srgb(1., 0., 0.),
+            ..default()
+        },
+        MaskFromSprite::default(),
+        AudioPlayer(assets.sound.clone()),
+        PlaybackSettings::LOOP,
+    ));
+}
+
+fn move_ufo(mut cmds: Commands, mut ufos: Query<(Entity, &mut Transform, &Ufo)>) {
+    for (entity, mut transform, ufo) in ufos.iter_mut() {
+        transform.translation.x += UFO_SPEED * ufo.direction;
+
+        if !(-UFO_SIZE.x..=ARENA_SIZE.x + UFO_SIZE.x).contains(&transform.translation.x) {
+            cmds.entity(entity).despawn();
+        }
+    }
+}
+
+/// Points for hitting the mystery ship after this many shots.
+pub fn mystery_points(shots_fired: usize) -> usize {
+    MYSTERY_POINTS[shots_fired % MYSTERY_POINTS.len()]
+}
+
+fn on_hit(
+    trigger: Trigger<Hit>,
+    mut cmds: Commands,
+    shots_fired: Res<ShotsFired>,
+    mut ufos: Query<&mut Points, With<Ufo>>,
+) {
+    let entity = trigger.entity();
+    let Ok(mut points) = ufos.get_mut(entity) else {
+        return;
+    };
+
+    points.0 = mystery_points(shots_fired.0);
+    cmds.entity(entity).despawn();
+}
+
+fn show_points(
+    trigger: Trigger<Hit>,
+    mut cmds: Commands,
+    assets: Res<UfoAssets>,
+    shots_fired: Res<ShotsFired>,
+    ufos: Query<&Transform, With<Ufo>>,
+) {
+    let Ok(transform) = ufos.get(trigger.entity()) else {
+        return;
+    };
+
+    cmds.spawn((
+        Text2d::new(mystery_points(shots_fired.0).to_string()),
+        TextFont {
+            font: assets.font.clone(),
+            font_size: 8.0,
+            font_smoothing: FontSmoothing::None,
+        },
+        TextColor(Color::srgb(1., 0., 0.)),
+        Transform::from_translation(transform.translation),
+        Temporary::from_seconds(1.),
+        StateScoped(InGame),
+    ));
+}
+
+fn despawn_ufo(mut cmds: Commands, ufos: Query<Entity, With<Ufo>>) {
+    for entity in ufos.iter() {
+        cmds.entity(entity).despawn();
+    }
+}
@@ -1,11 +1,15 @@
-use crate::{constants::*, shots::Collider, GameState};
+use crate::{
+    constants::*,
+    shots::{Collider, Layer},
+    InGame, NewGame,
+};
 use bevy::prelude::*;
 
 pub struct WallPlugin;
 
 impl Plugin for WallPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), startup);
+        app.add_systems(NewGame, startup);
     }
 }
 
@@ -19,15 +23,16 @@ fn startup(mut cmds: Commands) {
 #[derive(Component)]
 struct Wall;
 
-fn new(location: WallLocation) -> (Transform, Collider, Wall) {
+fn new(location: WallLocation) -> (Transform, Collider, Wall, StateScoped<InGame>) {
     (
         Transform {
             translation: location.position().extend(1.0),
             scale: location.size().extend(1.0),
             ..default()
         },
-        Collider::wall_layer(),
+        Collider::new(Layer::WALL),
         Wall,
+        StateScoped(InGame),
     )
 }
 
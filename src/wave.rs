@@ -0,0 +1,112 @@
+use crate::{GameState, NewGame};
+use bevy::{prelude::*, text::FontSmoothing};
+
+pub struct WavePlugin;
+
+/// How long the empty board is shown between two waves.
+const TRANSITION_SECONDS: f32 = 2.0;
+
+/// How far below the first wave's starting row each wave's formation starts. Mirrors the arcade's
+/// starting-row table, which grows for the first few waves and then repeats from the second entry.
+const START_DROP: [f32; 9] = [0., 16., 24., 32., 32., 32., 40., 40., 40.];
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Wave::default())
+            .add_systems(NewGame, reset)
+            .add_systems(OnEnter(GameState::LevelTransition), setup)
+            .add_systems(
+                FixedUpdate,
+                update.run_if(in_state(GameState::LevelTransition)),
+            )
+            .add_systems(OnExit(GameState::LevelTransition), cleanup);
+    }
+}
+
+/// The "Wave N" banner shown between waves.
+pub struct WavePresentationPlugin;
+
+impl Plugin for WavePresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::LevelTransition),
+            spawn_banner.after(setup),
+        );
+    }
+}
+
+/// The current wave, starting at 1.
+#[derive(Resource, Debug)]
+pub struct Wave(pub usize);
+
+impl Default for Wave {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl Wave {
+    /// Vertical offset to apply to the formation's starting position for this wave.
+    pub fn start_drop(&self) -> f32 {
+        let index = if self.0 <= START_DROP.len() {
+            self.0.saturating_sub(1)
+        } else {
+            1 + (self.0 - 2) % (START_DROP.len() - 1)
+        };
+
+        START_DROP[index]
+    }
+}
+
+#[derive(Resource)]
+struct TransitionTimer(Timer);
+
+fn reset(mut wave: ResMut<Wave>) {
+    *wave = Wave::default();
+}
+
+fn setup(mut cmds: Commands, mut wave: ResMut<Wave>) {
+    wave.0 += 1;
+
+    cmds.insert_resource(TransitionTimer(Timer::from_seconds(
+        TRANSITION_SECONDS,
+        TimerMode::Once,
+    )));
+}
+
+fn spawn_banner(mut cmds: Commands, wave: Res<Wave>, asset_server: Res<AssetServer>) {
+    let font = TextFont {
+        font: asset_server.load("font.ttf"),
+        font_size: 14.0,
+        font_smoothing: FontSmoothing::None,
+    };
+
+    cmds.spawn((
+        StateScoped(GameState::LevelTransition),
+        Node {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((Text::new(format!("Wave {}", wave.0)), font));
+    });
+}
+
+fn update(
+    time: Res<Time>,
+    mut timer: ResMut<TransitionTimer>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    if timer.0.tick(time.delta()).finished() {
+        next_game_state.set(GameState::Playing);
+    }
+}
+
+fn cleanup(mut cmds: Commands) {
+    cmds.remove_resource::<TransitionTimer>();
+}
@@ -0,0 +1,47 @@
+//! The high score is saved however a game ends, played headless under `MinimalPlugins` with the
+//! high score stored in a scratch data directory.
+
+use std::{env, fs};
+
+use bevy::prelude::*;
+use bevy_space_invaders::{
+    headless_app,
+    player::PlayerInput,
+    score::{HighScoreStoragePlugin, Score},
+    storage, GameState, PauseState,
+};
+
+#[test]
+fn quitting_from_the_pause_menu_saves_the_high_score() {
+    let data_home = env::temp_dir().join(format!("high-score-test-{}", std::process::id()));
+    env::set_var("XDG_DATA_HOME", &data_home);
+
+    let mut app = headless_app(1);
+    app.add_plugins(HighScoreStoragePlugin);
+
+    // fire straight up until an invader is shot down
+    let mut frames = 0;
+    while app.world().resource::<Score>().0 == 0 {
+        assert!(frames < 60 * 60, "nothing was shot down within a minute");
+        *app.world_mut().resource_mut::<PlayerInput>() = PlayerInput {
+            movement: 0.,
+            fire: frames % 7 == 0,
+        };
+        app.update();
+        frames += 1;
+    }
+    let score = app.world().resource::<Score>().0;
+
+    app.world_mut()
+        .resource_mut::<NextState<PauseState>>()
+        .set(PauseState::Paused);
+    app.update();
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::MainMenu);
+    app.update();
+
+    let saved = storage::load("high-score");
+    fs::remove_dir_all(&data_home).ok();
+    assert_eq!(saved, Some(score.to_string()));
+}
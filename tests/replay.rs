@@ -0,0 +1,61 @@
+//! Seeded games played headless under `MinimalPlugins`, checking that the rules play the same way
+//! every time and that replays of them don't desync.
+
+use std::time::Duration;
+
+use bevy::time::TimeUpdateStrategy;
+use bevy_space_invaders::{
+    headless_app,
+    player::PlayerInput,
+    replay::{Playback, Recorder, Replay},
+    rng::GameRng,
+};
+
+const SEED: u64 = 42;
+/// Half a minute of play, long enough for invaders to fire and shots to hit.
+const TICKS: usize = 30 * 60;
+
+/// Plays a seeded game one tick per update, sweeping the cannon from side to side and firing
+/// every few ticks, and returns its recording.
+fn record() -> Replay {
+    let mut app = headless_app(1);
+    app.insert_resource(GameRng::from_seed(SEED))
+        .insert_resource(Recorder::new(None));
+
+    for frame in 0..TICKS {
+        *app.world_mut().resource_mut::<PlayerInput>() = PlayerInput {
+            movement: if (frame / 120) % 2 == 0 { 1. } else { -1. },
+            fire: frame % 7 == 0,
+        };
+        app.update();
+    }
+
+    app.world().resource::<Recorder>().replay.clone()
+}
+
+#[test]
+fn seeded_games_play_the_same() {
+    let replay = record();
+    assert_eq!(replay.seed, SEED);
+    let checksums = replay.ticks.iter().filter(|tick| tick.checksum.is_some());
+    assert!(checksums.count() > 1);
+
+    assert_eq!(record(), replay);
+}
+
+#[test]
+fn replays_play_back_without_desync() {
+    let replay = Replay::from_bytes(&record().to_bytes()).unwrap();
+
+    // a different frame rate, so several ticks run in some updates and none in others
+    let mut app = headless_app(replay.players);
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1. / 45.,
+    )))
+    .insert_resource(Playback::new(replay));
+    while !app.world().resource::<Playback>().is_finished() {
+        app.update();
+    }
+
+    assert_eq!(app.world().resource::<Playback>().desync, None);
+}